@@ -1,5 +1,6 @@
 #!/usr/bin/env node
 import crypto from 'node:crypto'
+import { readFileSync } from 'node:fs'
 
 const [,, masterArg, postfixArg] = process.argv
 const master = masterArg || 'test'
@@ -11,7 +12,397 @@ const v1 = md5.toString('base64').replace(/=+$/,'')
 const sha = crypto.createHash('sha256').update(master + postfix).digest()
 const v2 = sha.toString('base64').replace(/=/g,'.').replace(/\+/g,'-').replace(/\//g,'_')
 
+const ALNUM = 'abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789'
+const STRONG = ALNUM + '!@#$%^&*()-_=+[]{};:,.?/<>~'
+
+function u32be(n) { const b = Buffer.alloc(4); b.writeUInt32BE(n); return b }
+function frame(fields) { return Buffer.concat(fields.flatMap(f => [u32be(f.length), Buffer.from(f)])) }
+
+// gen::extend_stream: SHA-256 over the last 32 bytes and a little-endian block counter
+function extendStream(seed, need) {
+  const out = [...seed]
+  for (let n = 1; out.length < need; n++) {
+    const le = Buffer.alloc(4); le.writeUInt32LE(n)
+    out.push(...crypto.createHash('sha256').update(Buffer.concat([Buffer.from(out.slice(-32)), le])).digest())
+  }
+  return out.slice(0, need)
+}
+
+// gen::map_to_alphabet: rejection sampling, extending the stream by 32 bytes when it runs dry
+function mapToAlphabet(stream, alpha, len) {
+  const m = alpha.length
+  const limit = Math.floor(255 / m) * m
+  let out = ''
+  for (let i = 0; out.length < len; i++) {
+    if (i >= stream.length) stream = extendStream(stream, stream.length + 32)
+    if (stream[i] < limit) out += alpha[stream[i] % m]
+  }
+  return out
+}
+
+function hmacStream(key, msg, need) {
+  const stream = []
+  for (let block = 0; stream.length < need; block++) {
+    stream.push(...crypto.createHmac('sha256', key).update(Buffer.concat([msg, u32be(block)])).digest())
+  }
+  return stream.slice(0, need)
+}
+
+const LOWER = 'abcdefghijklmnopqrstuvwxyz'
+const UPPER = 'ABCDEFGHIJKLMNOPQRSTUVWXYZ'
+const DIGITS = '0123456789'
+
+// Blake2b with any digest length up to 64 bytes; 64-bit words are (lo, hi) pairs of a Uint32Array
+const B2_IV = [0xf3bcc908, 0x6a09e667, 0x84caa73b, 0xbb67ae85, 0xfe94f82b, 0x3c6ef372, 0x5f1d36f1, 0xa54ff53a,
+  0xade682d1, 0x510e527f, 0x2b3e6c1f, 0x9b05688c, 0xfb41bd6b, 0x1f83d9ab, 0x137e2179, 0x5be0cd19]
+const B2_SIGMA = [
+  [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15], [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+  [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4], [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+  [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13], [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+  [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11], [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+  [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5], [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+]
+
+// v[a] += v[b] (+ w[c]), mod 2^64
+function add64(v, a, b, w = null, c = 0) {
+  const lo = v[2 * a] + v[2 * b] + (w ? w[2 * c] : 0)
+  v[2 * a + 1] += v[2 * b + 1] + (w ? w[2 * c + 1] : 0) + Math.floor(lo / 0x100000000)
+  v[2 * a] = lo
+}
+
+// v[d] = rotr64(v[d] ^ v[a], r)
+function xorRotr(v, d, a, r) {
+  let lo = v[2 * d] ^ v[2 * a], hi = v[2 * d + 1] ^ v[2 * a + 1]
+  if (r >= 32) { [lo, hi] = [hi, lo]; r -= 32 }
+  if (r) [lo, hi] = [lo >>> r | hi << (32 - r), hi >>> r | lo << (32 - r)]
+  v[2 * d] = lo; v[2 * d + 1] = hi
+}
+
+function blake2b(data, outLen) {
+  const h = new Uint32Array(B2_IV)
+  h[0] ^= 0x01010000 ^ outLen
+  const v = new Uint32Array(32), m = new Uint32Array(32)
+  const blocks = Math.max(1, Math.ceil(data.length / 128))
+  for (let n = 0; n < blocks; n++) {
+    const block = Buffer.alloc(128)
+    data.copy(block, 0, n * 128, (n + 1) * 128)
+    for (let i = 0; i < 32; i++) m[i] = block.readUInt32LE(4 * i)
+    v.set(h); v.set(B2_IV, 16)
+    const t = Math.min(data.length, (n + 1) * 128)
+    v[24] ^= t >>> 0; v[25] ^= Math.floor(t / 0x100000000)
+    if (n === blocks - 1) { v[28] = ~v[28]; v[29] = ~v[29] }
+    for (let r = 0; r < 12; r++) {
+      const s = B2_SIGMA[r % 10]
+      const g = (a, b, c, d, x, y) => {
+        add64(v, a, b, m, x); xorRotr(v, d, a, 32); add64(v, c, d); xorRotr(v, b, c, 24)
+        add64(v, a, b, m, y); xorRotr(v, d, a, 16); add64(v, c, d); xorRotr(v, b, c, 63)
+      }
+      g(0, 4, 8, 12, s[0], s[1]); g(1, 5, 9, 13, s[2], s[3]); g(2, 6, 10, 14, s[4], s[5]); g(3, 7, 11, 15, s[6], s[7])
+      g(0, 5, 10, 15, s[8], s[9]); g(1, 6, 11, 12, s[10], s[11]); g(2, 7, 8, 13, s[12], s[13]); g(3, 4, 9, 14, s[14], s[15])
+    }
+    for (let i = 0; i < 16; i++) h[i] ^= v[i] ^ v[i + 16]
+  }
+  const out = Buffer.alloc(64)
+  h.forEach((w, i) => out.writeUInt32LE(w, 4 * i))
+  return out.subarray(0, outLen)
+}
+
+// 32x32 -> 64-bit product as [lo, hi], from 16-bit halves so every step stays exact
+function mul32(x, y) {
+  const mid = (x >>> 16) * (y & 0xffff) + (x & 0xffff) * (y >>> 16)
+  const low = (x & 0xffff) * (y & 0xffff) + (mid % 0x10000) * 0x10000
+  return [low >>> 0, ((x >>> 16) * (y >>> 16) + Math.floor(mid / 0x10000) + Math.floor(low / 0x100000000)) >>> 0]
+}
+
+// Argon2 compression round: Blake2b's G without message words, with v[a] += v[b] + 2 * lo(a) * lo(b)
+function blaMkaRound(v, idx) {
+  const f = (a, b) => {
+    const [lo, hi] = mul32(v[2 * a], v[2 * b])
+    const sum = v[2 * a] + v[2 * b] + (lo << 1 >>> 0)
+    v[2 * a + 1] += v[2 * b + 1] + (hi << 1 | lo >>> 31) + Math.floor(sum / 0x100000000)
+    v[2 * a] = sum
+  }
+  const g = (a, b, c, d) => {
+    [a, b, c, d] = [idx[a], idx[b], idx[c], idx[d]]
+    f(a, b); xorRotr(v, d, a, 32); f(c, d); xorRotr(v, b, c, 24)
+    f(a, b); xorRotr(v, d, a, 16); f(c, d); xorRotr(v, b, c, 63)
+  }
+  g(0, 4, 8, 12); g(1, 5, 9, 13); g(2, 6, 10, 14); g(3, 7, 11, 15)
+  g(0, 5, 10, 15); g(1, 6, 11, 12); g(2, 7, 8, 13); g(3, 4, 9, 14)
+}
+
+const ROWS = [...Array(8)].map((_, i) => [...Array(16)].map((_, j) => 16 * i + j))
+const COLUMNS = [...Array(8)].map((_, i) => [...Array(16)].map((_, j) => 2 * i + 16 * (j >> 1) + (j & 1)))
+
+// out = P(x ^ y) ^ x ^ y (^ out when xoring over a previous pass); blocks are 256-word views
+function fillBlock(out, x, y, withXor) {
+  const r = x.map((w, i) => w ^ y[i])
+  const tmp = withXor ? r.map((w, i) => w ^ out[i]) : r.slice()
+  for (const row of ROWS) blaMkaRound(r, row)
+  for (const col of COLUMNS) blaMkaRound(r, col)
+  out.set(r.map((w, i) => w ^ tmp[i]))
+}
+
+function le32(n) { const b = Buffer.alloc(4); b.writeUInt32LE(n); return b }
+
+// Argon2 H': Blake2b for up to 64 bytes, otherwise a chain of 64-byte hashes keeping 32 bytes of each
+function hPrime(x, len) {
+  const input = Buffer.concat([le32(len), x])
+  if (len <= 64) return blake2b(input, len)
+  const r = Math.ceil(len / 32) - 2
+  let v = blake2b(input, 64)
+  const out = [v.subarray(0, 32)]
+  for (let i = 1; i < r; i++) { v = blake2b(v, 64); out.push(v.subarray(0, 32)) }
+  return Buffer.concat([...out, blake2b(v, len - 32 * r)])
+}
+
+// Argon2id, version 0x13 (RFC 9106), without secret or associated data
+function argon2id(password, salt, m, t, p, tagLen) {
+  const h0 = blake2b(Buffer.concat([le32(p), le32(tagLen), le32(m), le32(t), le32(0x13), le32(2),
+    le32(password.length), password, le32(salt.length), salt, le32(0), le32(0)]), 64)
+  const q = Math.floor(m / (4 * p)) * 4, seg = q / 4
+  const mem = new Uint32Array(q * p * 256)
+  const block = i => mem.subarray(i * 256, (i + 1) * 256)
+  const toWords = b => Uint32Array.from({ length: b.length / 4 }, (_, i) => b.readUInt32LE(4 * i))
+  for (let lane = 0; lane < p; lane++) {
+    for (let j = 0; j < 2; j++) block(lane * q + j).set(toWords(hPrime(Buffer.concat([h0, le32(j), le32(lane)]), 1024)))
+  }
+  const zero = new Uint32Array(256), input = new Uint32Array(256), addresses = new Uint32Array(256)
+  for (let pass = 0; pass < t; pass++) {
+    for (let slice = 0; slice < 4; slice++) {
+      for (let lane = 0; lane < p; lane++) {
+        // the first half of the first pass picks reference blocks independently of the data
+        const independent = pass === 0 && slice < 2
+        const nextAddresses = () => { input[12]++; fillBlock(addresses, zero, input, false); fillBlock(addresses, zero, addresses, false) }
+        input.fill(0)
+        ;[pass, lane, slice, q * p, t, 2].forEach((n, i) => { input[2 * i] = n })
+        let first = 0
+        if (pass === 0 && slice === 0) { first = 2; if (independent) nextAddresses() }
+        for (let i = first; i < seg; i++) {
+          const cur = lane * q + slice * seg + i
+          const prev = cur % q === 0 ? cur + q - 1 : cur - 1
+          if (independent && i % 128 === 0) nextAddresses()
+          const [j1, j2] = independent ? [addresses[2 * (i % 128)], addresses[2 * (i % 128) + 1]] : [mem[prev * 256], mem[prev * 256 + 1]]
+          const refLane = pass === 0 && slice === 0 ? lane : j2 % p
+          const done = pass === 0 ? slice * seg : q - seg
+          const area = refLane === lane ? done + i - 1 : done - (i === 0 ? 1 : 0)
+          const rel = area - 1 - mul32(area, mul32(j1, j1)[1])[1]
+          const start = pass === 0 || slice === 3 ? 0 : (slice + 1) * seg
+          fillBlock(block(cur), block(prev), block(refLane * q + (start + rel) % q), pass !== 0)
+        }
+      }
+    }
+  }
+  const last = block(q - 1).slice()
+  for (let lane = 1; lane < p; lane++) block(lane * q + q - 1).forEach((w, i) => { last[i] ^= w })
+  const bytes = Buffer.alloc(1024)
+  last.forEach((w, i) => bytes.writeUInt32LE(w, 4 * i))
+  return hPrime(bytes, tagLen)
+}
+
+// Key stream behind the len*, v3 and argon methods (gen::method_stream)
+function sizedStream(master, postfix, spec, counter, need) {
+  const counterField = counter ? [u32be(counter)] : []
+  const nfc = Buffer.from(master.normalize('NFC'))
+  switch (spec.family) {
+    // len*: SHA-256 chain over the raw `master::postfix::method` string
+    case 'len': return extendStream(crypto.createHash('sha256').update(`${master}::${postfix}::${spec.method}`).digest(), need)
+    // v3: HMAC-SHA256(master, frame || block_be32)
+    case 'v3': return hmacStream(nfc, frame([Buffer.from('saforia/v3'), Buffer.from(postfix.normalize('NFC')), Buffer.from(spec.charset), u32be(spec.len), ...counterField]), need)
+    // argon: Argon2id of the master salted with the same frame, as long as the caller needs
+    case 'argon': {
+      const salt = frame([Buffer.from('saforia/argon'), Buffer.from(postfix.normalize('NFC')), Buffer.from(spec.charset), u32be(spec.len), ...counterField])
+      return [...argon2id(nfc, salt, spec.m, spec.t, spec.p, need)]
+    }
+  }
+}
+
+function sized(method) {
+  let m
+  if ((m = /^len(\d+)_(alnum|strong)$/.exec(method))) return { family: 'len', method, len: +m[1], charset: m[2] }
+  if ((m = /^v3_len(\d+)_(alnum|strong)$/.exec(method))) return { family: 'v3', len: +m[1], charset: m[2] }
+  if ((m = /^argon(?:_m(\d+)_t(\d+)_p(\d+))?_len(\d+)_(alnum|strong)$/.exec(method))) {
+    return { family: 'argon', m: +(m[1] ?? 19456), t: +(m[2] ?? 2), p: +(m[3] ?? 1), len: +m[4], charset: m[5] }
+  }
+  return null
+}
+
+function v3(master, postfix, len, charset, counter = 0) {
+  const spec = { family: 'v3', len, charset }
+  return mapToAlphabet(sizedStream(master, postfix, spec, counter, len * 2), charset === 'strong' ? STRONG : ALNUM, len)
+}
+
+// Policy: generate-and-test over HMAC(key, frame(tag, alphabet, len, attempt)) candidates
+function policy(p, spec, key) {
+  const min = Math.max(p.min_len ?? 1, 1), max = Math.min(p.max_len ?? 256, 256)
+  let symbols = [...new Set(p.symbols ?? '!@#$%^&*()-_=+[]{};:,.?/<>~')].join('')
+  if (spec.charset === 'alnum' && !p.require_symbol) symbols = ''
+  const keep = set => [...set].filter(c => !(p.forbidden ?? '').includes(c)).join('')
+  const classes = [[LOWER, p.require_lower], [UPPER, p.require_upper], [DIGITS, p.require_digit], [symbols, p.require_symbol]]
+    .map(([set, needed]) => [keep(set), needed])
+  const alphabet = classes.map(([set]) => set).join('')
+  const len = Math.min(Math.max(spec.len, min), max)
+  for (let attempt = 0; attempt < 1000; attempt++) {
+    const msg = frame([Buffer.from('saforia/policy'), Buffer.from(alphabet), u32be(len), u32be(attempt)])
+    const candidate = mapToAlphabet(hmacStream(Buffer.from(key), msg, len * 2), alphabet, len)
+    if (classes.every(([set, needed]) => !needed || [...candidate].some(c => set.includes(c)))) return candidate
+  }
+  throw new Error('policy could not be satisfied')
+}
+
+// pin: 16 PIN-sized chunks of one digit stream; `_safe` skips runs, repeats and 19xx/20xx
+function pin(master, postfix, len, safe, counter = 0) {
+  const msg = frame([Buffer.from('saforia/pin'), Buffer.from(postfix.normalize('NFC')), u32be(len), ...(counter ? [u32be(counter)] : [])])
+  const digits = mapToAlphabet(hmacStream(Buffer.from(master.normalize('NFC')), msg, len * 32), '0123456789', len * 16)
+  const trivial = p => {
+    const steps = [...p].slice(1).map((c, i) => c - p[i])
+    return steps.every(d => d === steps[0] && Math.abs(d) <= 1) || (p.length === 4 && /^(19|20)/.test(p))
+  }
+  return digits.match(new RegExp(`.{${len}}`, 'g')).find(p => !safe || !trivial(p))
+}
+
+// bip39: HMAC-SHA256 entropy, then the SHA-256 checksum bits, as 11-bit word indices
+function bip39(master, postfix, words) {
+  const list = readFileSync(new URL('../src-tauri/src/bip39_english.txt', import.meta.url), 'utf8').split('\n')
+  const entropy = hmacStream(Buffer.from(master.normalize('NFC')), frame([Buffer.from('saforia/bip39'), Buffer.from(postfix.normalize('NFC')), u32be(words)]), words * 4 / 3)
+  const check = crypto.createHash('sha256').update(Buffer.from(entropy)).digest()[0]
+  const bits = [...entropy, check].map(b => b.toString(2).padStart(8, '0')).join('').slice(0, words * 11)
+  return bits.match(/.{11}/g).map(b => list[parseInt(b, 2)]).join(' ')
+}
+
+// words: big-endian u16 draws below 8 * 7776 pick an EFF word, the rest are rejected
+function effWords(master, postfix, words, sep, cap) {
+  const list = readFileSync(new URL('../src-tauri/src/eff_large_wordlist.txt', import.meta.url), 'utf8').split('\n').filter(Boolean).map(l => l.split('\t')[1])
+  let stream = hmacStream(Buffer.from(master.normalize('NFC')), frame([Buffer.from('saforia/words'), Buffer.from(postfix.normalize('NFC')), u32be(words)]), words * 4)
+  const out = []
+  for (let i = 0; out.length < words; i += 2) {
+    if (i + 2 > stream.length) stream = extendStream(stream, stream.length + 32)
+    const v = stream[i] << 8 | stream[i + 1]
+    if (v < 8 * 7776) out.push(cap ? list[v % 7776][0].toUpperCase() + list[v % 7776].slice(1) : list[v % 7776])
+  }
+  return out.join({ dash: '-', dot: '.', space: ' ', underscore: '_' }[sep])
+}
+
+const legacy = (master, postfix, method) => {
+  const [v1, v2] = [crypto.createHash('md5'), crypto.createHash('sha256')].map(h => h.update(master + postfix).digest('base64'))
+  return method === 'legacy_v1' ? v1.replace(/=+$/, '') : v2.replace(/=/g, '.').replace(/\+/g, '-').replace(/\//g, '_')
+}
+
+// Big-endian integer divided in place by a small divisor, returning the remainder
+function divmod(n, d) {
+  let rem = 0
+  for (let i = 0; i < n.length; i++) { const cur = rem * 256 + n[i]; n[i] = Math.floor(cur / d); rem = cur % d }
+  return rem
+}
+
+// LessPass v2: PBKDF2-SHA256 entropy rendered by repeated division, one extra character per rule
+function lessPass(master, site, profile) {
+  const o = { login: '', length: 16, counter: 1, lowercase: true, uppercase: true, digits: true, symbols: true, ...profile }
+  const entropy = crypto.pbkdf2Sync(master, site + o.login + o.counter.toString(16), 100000, 32, 'sha256')
+  const rules = [[o.lowercase, LOWER], [o.uppercase, UPPER], [o.digits, DIGITS], [o.symbols, '!"#$%&\'()*+,-./:;<=>?@[\\]^_`{|}~']]
+    .filter(([on]) => on).map(([, set]) => set)
+  const all = rules.join('')
+  const out = [...Array(o.length - rules.length)].map(() => all[divmod(entropy, all.length)])
+  const extra = rules.map(set => set[divmod(entropy, set.length)])
+  for (const c of extra) out.splice(divmod(entropy, out.length), 0, c)
+  return out.join('')
+}
+
+// Spectre (Master Password v3), long template: scrypt master key, HMAC-SHA256 site seed
+const SPECTRE_LONG = ['CvcvnoCvcvCvcv', 'CvcvCvcvnoCvcv', 'CvcvCvcvCvcvno', 'CvccnoCvcvCvcv', 'CvccCvcvnoCvcv',
+  'CvccCvcvCvcvno', 'CvcvnoCvccCvcv', 'CvcvCvccnoCvcv', 'CvcvCvccCvcvno', 'CvcvnoCvcvCvcc', 'CvcvCvcvnoCvcc',
+  'CvcvCvcvCvccno', 'CvccnoCvccCvcv', 'CvccCvccnoCvcv', 'CvccCvccCvcvno', 'CvcvnoCvccCvcc', 'CvcvCvccnoCvcc',
+  'CvcvCvccCvccno', 'CvccnoCvcvCvcc', 'CvccCvcvnoCvcc', 'CvccCvcvCvccno']
+const SPECTRE_CLASSES = { V: 'AEIOU', C: 'BCDFGHJKLMNPQRSTVWXYZ', v: 'aeiou', c: 'bcdfghjklmnpqrstvwxyz', n: DIGITS, o: "@&%?,=[]_:-+*$#!'^~;()/." }
+
+function spectre(master, site, profile) {
+  const o = { counter: 1, template: 'long', ...profile }
+  if (o.template !== 'long') throw new Error(`spectre template ${o.template} is not implemented here`)
+  const scope = Buffer.from('com.lyndir.masterpassword')
+  const name = Buffer.from(o.full_name)
+  const key = crypto.scryptSync(master, Buffer.concat([scope, u32be(name.length), name]), 64, { N: 32768, r: 8, p: 2, maxmem: 64 * 1024 * 1024 })
+  const siteBytes = Buffer.from(site)
+  const seed = crypto.createHmac('sha256', key).update(Buffer.concat([scope, u32be(siteBytes.length), siteBytes, u32be(o.counter)])).digest()
+  const pattern = SPECTRE_LONG[seed[0] % SPECTRE_LONG.length]
+  return [...pattern].map((c, i) => SPECTRE_CLASSES[c][seed[i + 1] % SPECTRE_CLASSES[c].length]).join('')
+}
+
+// Registrable domain as the bookmarklets compute it: two labels, three under a listed `sld.tld`
+function reduceDomain(postfix) {
+  const suffixes = readFileSync(new URL('../src-tauri/src/bookmarklet_domains.txt', import.meta.url), 'utf8').split('\n')
+  let host = postfix.trim().toLowerCase()
+  host = host.includes('://') ? host.slice(host.indexOf('://') + 3) : host
+  host = host.split(/[/?#]/)[0].split('@').pop().split(':')[0].replace(/^\.+|\.+$/g, '')
+  const labels = host.split('.')
+  if (labels.length === 4 && labels.every(l => /^\d+$/.test(l) && +l < 256)) return host
+  const keep = labels.length >= 3 && suffixes.includes(labels.slice(-2).join('.')) ? 3 : 2
+  return labels.slice(-keep).join('.')
+}
+
+// SuperGenPass: at least 10 rounds of base64(hash) with + -> 9, / -> 8, = -> A
+function sgp(master, domain, hash, len) {
+  const valid = p => /^[a-z]/.test(p) && /[A-Z]/.test(p) && /[0-9]/.test(p)
+  let h = `${master}:${domain}`
+  for (let round = 0; round < 10000; round++) {
+    if (round >= 10 && valid(h.slice(0, len))) return h.slice(0, len)
+    h = crypto.createHash(hash).update(h).digest('base64').replace(/\+/g, '9').replace(/\//g, '8').replace(/=/g, 'A')
+  }
+  throw new Error('supergenpass found no valid password')
+}
+
+// PwdHash: HMAC-MD5 over the low byte of each UTF-16 unit, then pwdhash.js's fixups and rotation
+function pwdhash(master, domain) {
+  const low = s => Buffer.from([...Array(s.length)].map((_, i) => s.charCodeAt(i) & 0xff))
+  const hash = crypto.createHmac('md5', low(master)).update(low(domain)).digest('base64').replace(/=+$/, '')
+  const nonWord = c => /\W/.test(c)
+  const nonalphanumeric = /\W/.test(master)
+  const start = Math.min(Math.max(master.length + 2 - 4, 0), hash.length)
+  const out = [...hash.slice(0, start)]
+  const extras = [...hash.slice(start)]
+  const next = () => extras.length ? extras.shift().charCodeAt(0) : 0
+  out.push(out.some(c => /[A-Z]/.test(c)) ? String.fromCharCode(next()) : String.fromCharCode(65 + next() % 26))
+  out.push(out.some(c => /[a-z]/.test(c)) ? String.fromCharCode(next()) : String.fromCharCode(97 + next() % 26))
+  out.push(out.some(c => /[0-9]/.test(c)) ? String.fromCharCode(next()) : String.fromCharCode(48 + next() % 10))
+  out.push(out.some(nonWord) && nonalphanumeric ? String.fromCharCode(next()) : '+')
+  if (!nonalphanumeric) for (let i; (i = out.findIndex(nonWord)) >= 0;) out[i] = String.fromCharCode(65 + next() % 26)
+  const by = next() % out.length
+  return [...out.slice(by), ...out.slice(0, by)].join('')
+}
+
+// Independent implementation of every method family in the corpus; anything else is an error
+// so a new family cannot go unchecked
+function derive(v) {
+  let m, spec
+  if ((spec = sized(v.method))) {
+    const stream = need => sizedStream(v.master, v.postfix, spec, v.counter ?? 0, need)
+    if (v.policy) return policy(v.policy, spec, stream(32))
+    return mapToAlphabet(stream(spec.len * 2), spec.charset === 'strong' ? STRONG : ALNUM, spec.len)
+  }
+  if (v.policy) throw new Error(`policy on ${v.method}`)
+  if (v.method === 'legacy_v1' || v.method === 'legacy_v2') return legacy(v.master, v.postfix, v.method)
+  if ((m = /^pin_len(\d+)(_safe)?$/.exec(v.method))) return pin(v.master, v.postfix, +m[1], !!m[2], v.counter)
+  if ((m = /^bip39_words(\d+)$/.exec(v.method))) return bip39(v.master, v.postfix, +m[1])
+  if ((m = /^words(\d+)_(dash|dot|space|underscore)(_cap)?$/.exec(v.method))) return effWords(v.master, v.postfix, +m[1], m[2], !!m[3])
+  if (v.method === 'lesspass') return lessPass(v.master, v.postfix, v.lesspass)
+  if (v.method === 'spectre') return spectre(v.master, v.postfix, v.spectre)
+  if ((m = /^sgp_(md5|sha512)_len(\d+)$/.exec(v.method))) return sgp(v.master, reduceDomain(v.postfix), m[1], +m[2])
+  if (v.method === 'pwdhash') return pwdhash(v.master, reduceDomain(v.postfix))
+  throw new Error(`no independent implementation of ${v.method}`)
+}
+
+// Known-answer corpus shared with src-tauri/src/gen.rs (test and `self_test` command)
+const corpus = JSON.parse(readFileSync(new URL('../src-tauri/src/known_answers.json', import.meta.url), 'utf8'))
+
 console.log('Legacy v1 (MD5+Base64 no =):', v1)
 console.log('Legacy v2 (SHA256+URL-Base64):', v2)
+console.log('v3 len20 strong (HMAC-SHA256):', v3(master, postfix, 20, 'strong'))
 console.log(`\nInputs -> master:"${master}", postfix:"${postfix}"`)
 
+let failed = 0
+for (const v of corpus.vectors) {
+  let got
+  try { got = derive(v) } catch (e) { failed++; console.error(`KAT unchecked ${v.method} ${JSON.stringify(v.postfix)}: ${e.message}`); continue }
+  if (got !== v.expected) { failed++; console.error(`KAT mismatch ${v.method} ${JSON.stringify(v.postfix)}: got ${got}, expected ${v.expected}`) }
+}
+if (failed) process.exit(1)
+console.log(`Known-answer vectors (corpus v${corpus.version}): all ${corpus.vectors.length} checked, OK`)
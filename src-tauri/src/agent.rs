@@ -0,0 +1,334 @@
+// Minimal ssh-agent (draft-miller-ssh-agent) serving the Ed25519 keys of saved entries.
+// Only REQUEST_IDENTITIES and SIGN_REQUEST are answered; adding/removing keys, agent
+// locking and extensions get SSH_AGENT_FAILURE, so the key set is whatever was unlocked.
+
+use serde::Serialize;
+use signature::Signer;
+use ssh_key::{PrivateKey, Signature};
+use std::{io, path::{Path, PathBuf}, sync::{Arc, Mutex}, thread, time::{Duration, Instant}};
+
+#[cfg(unix)]
+use std::{fs, io::{Read, Write}, os::unix::{fs::{DirBuilderExt, FileTypeExt, MetadataExt, PermissionsExt}, net::{UnixListener, UnixStream}}};
+
+const SSH_AGENT_FAILURE: u8 = 5;
+const SSH_AGENTC_REQUEST_IDENTITIES: u8 = 11;
+const SSH_AGENT_IDENTITIES_ANSWER: u8 = 12;
+const SSH_AGENTC_SIGN_REQUEST: u8 = 13;
+const SSH_AGENT_SIGN_RESPONSE: u8 = 14;
+// OpenSSH's own agent refuses anything larger
+const MAX_MESSAGE_LEN: usize = 256 * 1024;
+
+pub const DEFAULT_LOCK_AFTER: Duration = Duration::from_secs(15 * 60);
+
+pub struct Identity {
+    key: PrivateKey,
+    blob: Vec<u8>,
+}
+
+impl Identity {
+    pub fn new(key: PrivateKey) -> Result<Self, ssh_key::Error> {
+        let blob = key.public_key().to_bytes()?;
+        Ok(Identity { key, blob })
+    }
+}
+
+#[derive(Default)]
+struct Keys {
+    identities: Vec<Identity>,
+    lock_at: Option<Instant>,
+}
+
+impl Keys {
+    // Drops the keys (zeroized by ssh-key) once the unlock window has passed
+    fn expire(&mut self) {
+        if self.lock_at.is_some_and(|t| Instant::now() >= t) { *self = Keys::default(); }
+    }
+}
+
+#[derive(Serialize)]
+pub struct AgentStatus {
+    /// Socket path to export as `SSH_AUTH_SOCK`, once the agent has been started
+    pub socket: Option<String>,
+    pub unlocked: bool,
+    pub keys: usize,
+    pub locks_in_seconds: Option<u64>,
+}
+
+/// Shared agent state: the listener lives for the rest of the process once started,
+/// while keys come and go with `unlock`/`lock` and the auto-lock timer.
+#[derive(Default)]
+pub struct SshAgent {
+    keys: Arc<Mutex<Keys>>,
+    socket: Mutex<Option<PathBuf>>,
+}
+
+impl SshAgent {
+    /// Binds `path` on first use (replacing a stale socket left by a previous run) and
+    /// serves it from a background thread; later calls return the bound path. The parent
+    /// directory is made private first, so no other user can connect in the moment between
+    /// bind and chmod, or put something else at `path`.
+    #[cfg(unix)]
+    pub fn listen(&self, path: &Path) -> io::Result<PathBuf> {
+        let mut socket = self.socket.lock().unwrap();
+        if let Some(bound) = socket.as_ref() { return Ok(bound.clone()); }
+        private_dir(path.parent().unwrap_or(Path::new(".")))?;
+        if let Ok(meta) = fs::symlink_metadata(path) {
+            if !meta.file_type().is_socket() || meta.uid() != current_uid() {
+                return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} exists and is not our socket", path.display())));
+            }
+            fs::remove_file(path)?;
+        }
+        let listener = UnixListener::bind(path)?;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        let keys = Arc::clone(&self.keys);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let keys = Arc::clone(&keys);
+                thread::spawn(move || { let _ = serve(stream, &keys); });
+            }
+        });
+        *socket = Some(path.to_path_buf());
+        Ok(path.to_path_buf())
+    }
+
+    #[cfg(not(unix))]
+    pub fn listen(&self, _path: &Path) -> io::Result<PathBuf> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "the ssh-agent needs Unix domain sockets"))
+    }
+
+    /// Replaces the served keys and locks again after `lock_after`.
+    pub fn unlock(&self, identities: Vec<Identity>, lock_after: Duration) {
+        *self.keys.lock().unwrap() = Keys { identities, lock_at: Some(Instant::now() + lock_after) };
+        // A later unlock moves `lock_at`, so this timer then finds nothing to expire
+        let keys = Arc::clone(&self.keys);
+        thread::spawn(move || {
+            thread::sleep(lock_after);
+            keys.lock().unwrap().expire();
+        });
+    }
+
+    pub fn lock(&self) { *self.keys.lock().unwrap() = Keys::default(); }
+
+    pub fn status(&self) -> AgentStatus {
+        let mut keys = self.keys.lock().unwrap();
+        keys.expire();
+        AgentStatus {
+            socket: self.socket.lock().unwrap().as_ref().map(|p| p.display().to_string()),
+            unlocked: keys.lock_at.is_some(),
+            keys: keys.identities.len(),
+            locks_in_seconds: keys.lock_at.map(|t| t.saturating_duration_since(Instant::now()).as_secs()),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn current_uid() -> u32 {
+    // SAFETY: geteuid has no preconditions and cannot fail
+    unsafe { libc::geteuid() }
+}
+
+// Creates `dir` at mode 0700, or narrows an existing one to it; a directory owned by someone
+// else (or a symlink) is refused, as its owner could still swap the socket
+#[cfg(unix)]
+fn private_dir(dir: &Path) -> io::Result<()> {
+    match fs::DirBuilder::new().mode(0o700).create(dir) {
+        Err(e) if e.kind() != io::ErrorKind::AlreadyExists => return Err(e),
+        _ => {}
+    }
+    let meta = fs::symlink_metadata(dir)?;
+    if !meta.is_dir() || meta.uid() != current_uid() {
+        return Err(io::Error::new(io::ErrorKind::PermissionDenied, format!("{} is not a directory of this user", dir.display())));
+    }
+    if meta.mode() & 0o077 != 0 { fs::set_permissions(dir, fs::Permissions::from_mode(0o700))?; }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn serve(mut stream: UnixStream, keys: &Mutex<Keys>) -> io::Result<()> {
+    loop {
+        let mut len = [0u8; 4];
+        match stream.read_exact(&mut len) {
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            r => r?,
+        }
+        let len = u32::from_be_bytes(len) as usize;
+        if len == 0 || len > MAX_MESSAGE_LEN {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "bad agent message length"));
+        }
+        let mut msg = vec![0u8; len];
+        stream.read_exact(&mut msg)?;
+        let reply = handle(&msg, keys);
+        stream.write_all(&(reply.len() as u32).to_be_bytes())?;
+        stream.write_all(&reply)?;
+    }
+}
+
+fn handle(msg: &[u8], keys: &Mutex<Keys>) -> Vec<u8> {
+    let mut keys = keys.lock().unwrap();
+    keys.expire();
+    match msg[0] {
+        SSH_AGENTC_REQUEST_IDENTITIES => {
+            let mut out = vec![SSH_AGENT_IDENTITIES_ANSWER];
+            out.extend_from_slice(&(keys.identities.len() as u32).to_be_bytes());
+            for id in &keys.identities {
+                put_string(&mut out, &id.blob);
+                put_string(&mut out, id.key.comment().as_bytes());
+            }
+            out
+        }
+        SSH_AGENTC_SIGN_REQUEST => sign(&msg[1..], &keys.identities).unwrap_or_else(|| vec![SSH_AGENT_FAILURE]),
+        _ => vec![SSH_AGENT_FAILURE],
+    }
+}
+
+// string key_blob, string data, uint32 flags -> string signature(string algorithm, string sig)
+fn sign(mut body: &[u8], identities: &[Identity]) -> Option<Vec<u8>> {
+    let blob = take_string(&mut body)?;
+    let data = take_string(&mut body)?;
+    // flags only select RSA hash variants, which Ed25519 does not have
+    let _flags = take_u32(&mut body)?;
+    let id = identities.iter().find(|i| i.blob == blob)?;
+    let sig: Signature = id.key.try_sign(data).ok()?;
+    let mut encoded = Vec::new();
+    put_string(&mut encoded, sig.algorithm().as_str().as_bytes());
+    put_string(&mut encoded, sig.as_bytes());
+    let mut out = vec![SSH_AGENT_SIGN_RESPONSE];
+    put_string(&mut out, &encoded);
+    Some(out)
+}
+
+fn put_string(out: &mut Vec<u8>, s: &[u8]) {
+    out.extend_from_slice(&(s.len() as u32).to_be_bytes());
+    out.extend_from_slice(s);
+}
+
+fn take_u32(buf: &mut &[u8]) -> Option<u32> {
+    let (head, rest) = buf.split_first_chunk::<4>()?;
+    *buf = rest;
+    Some(u32::from_be_bytes(*head))
+}
+
+fn take_string<'a>(buf: &mut &'a [u8]) -> Option<&'a [u8]> {
+    let len = take_u32(buf)? as usize;
+    if buf.len() < len { return None; }
+    let (s, rest) = buf.split_at(len);
+    *buf = rest;
+    Some(s)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::ssh;
+    use ssh_key::{public::KeyData, PublicKey};
+    use signature::Verifier;
+
+    fn request(stream: &mut UnixStream, msg: &[u8]) -> Vec<u8> {
+        stream.write_all(&(msg.len() as u32).to_be_bytes()).unwrap();
+        stream.write_all(msg).unwrap();
+        let mut len = [0u8; 4];
+        stream.read_exact(&mut len).unwrap();
+        let mut reply = vec![0u8; u32::from_be_bytes(len) as usize];
+        stream.read_exact(&mut reply).unwrap();
+        reply
+    }
+
+    fn sign_request(blob: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut msg = vec![SSH_AGENTC_SIGN_REQUEST];
+        put_string(&mut msg, blob);
+        put_string(&mut msg, data);
+        msg.extend_from_slice(&0u32.to_be_bytes());
+        msg
+    }
+
+    fn identity(postfix: &str) -> Identity {
+        Identity::new(ssh::ed25519_key("test", postfix, 0, postfix).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn lists_and_signs_until_locked() {
+        let dir = tempfile::tempdir().unwrap();
+        let agent = SshAgent::default();
+        let path = agent.listen(&dir.path().join("agent.sock")).unwrap();
+        let mut stream = UnixStream::connect(&path).unwrap();
+
+        // nothing is served before the first unlock
+        assert_eq!(request(&mut stream, &[SSH_AGENTC_REQUEST_IDENTITIES]), [SSH_AGENT_IDENTITIES_ANSWER, 0, 0, 0, 0]);
+
+        agent.unlock(vec![identity("example"), identity("github.com")], DEFAULT_LOCK_AFTER);
+        let reply = request(&mut stream, &[SSH_AGENTC_REQUEST_IDENTITIES]);
+        let mut body = &reply[1..];
+        assert_eq!(reply[0], SSH_AGENT_IDENTITIES_ANSWER);
+        assert_eq!(take_u32(&mut body), Some(2));
+        let blob = take_string(&mut body).unwrap().to_vec();
+        assert_eq!(take_string(&mut body), Some(&b"example"[..]));
+        let public = PublicKey::from_bytes(&blob).unwrap();
+        assert_eq!(public.fingerprint(ssh_key::HashAlg::Sha256).to_string(), "SHA256:NJHnLP1dyQDM0SNvVETHbgVEc2T2KjNAeIAU1gA/zx8");
+
+        let reply = request(&mut stream, &sign_request(&blob, b"challenge"));
+        assert_eq!(reply[0], SSH_AGENT_SIGN_RESPONSE);
+        let mut body = &reply[1..];
+        let mut sig = take_string(&mut body).unwrap();
+        assert_eq!(take_string(&mut sig), Some(&b"ssh-ed25519"[..]));
+        let sig = Signature::new(ssh_key::Algorithm::Ed25519, take_string(&mut sig).unwrap()).unwrap();
+        let KeyData::Ed25519(key) = public.key_data() else { panic!("not ed25519") };
+        assert!(key.verify(b"challenge", &sig).is_ok());
+        assert!(key.verify(b"other", &sig).is_err());
+
+        // unknown keys, malformed requests and unsupported messages fail without closing the stream
+        assert_eq!(request(&mut stream, &sign_request(b"nope", b"challenge")), [SSH_AGENT_FAILURE]);
+        assert_eq!(request(&mut stream, &[SSH_AGENTC_SIGN_REQUEST, 0, 0]), [SSH_AGENT_FAILURE]);
+        assert_eq!(request(&mut stream, &[17]), [SSH_AGENT_FAILURE]);
+
+        agent.lock();
+        assert_eq!(request(&mut stream, &[SSH_AGENTC_REQUEST_IDENTITIES]), [SSH_AGENT_IDENTITIES_ANSWER, 0, 0, 0, 0]);
+        assert_eq!(request(&mut stream, &sign_request(&blob, b"challenge")), [SSH_AGENT_FAILURE]);
+    }
+
+    #[test]
+    fn locks_itself_after_the_timeout() {
+        let agent = SshAgent::default();
+        agent.unlock(vec![identity("example")], Duration::from_millis(50));
+        assert_eq!(agent.status().keys, 1);
+        thread::sleep(Duration::from_millis(200));
+        let status = agent.status();
+        assert!(!status.unlocked);
+        assert_eq!(status.keys, 0);
+        // an earlier timer must not cut a later unlock short
+        agent.unlock(vec![identity("example")], Duration::from_millis(50));
+        agent.unlock(vec![identity("example")], DEFAULT_LOCK_AFTER);
+        thread::sleep(Duration::from_millis(200));
+        assert_eq!(agent.status().keys, 1);
+    }
+
+    #[test]
+    fn refuses_to_replace_a_regular_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("agent.sock");
+        fs::write(&path, b"not a socket").unwrap();
+        assert!(SshAgent::default().listen(&path).is_err());
+        assert_eq!(fs::read(&path).unwrap(), b"not a socket");
+    }
+
+    #[test]
+    fn binds_inside_a_private_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let sock_dir = dir.path().join("agent");
+        fs::create_dir(&sock_dir).unwrap();
+        fs::set_permissions(&sock_dir, fs::Permissions::from_mode(0o755)).unwrap();
+        let path = SshAgent::default().listen(&sock_dir.join("agent.sock")).unwrap();
+        assert_eq!(fs::metadata(&sock_dir).unwrap().mode() & 0o777, 0o700);
+        assert_eq!(fs::metadata(&path).unwrap().mode() & 0o777, 0o600);
+
+        // our own socket from an earlier run is replaced, and a missing directory is created
+        let again = SshAgent::default().listen(&path).unwrap();
+        assert!(UnixStream::connect(&again).is_ok());
+        assert!(SshAgent::default().listen(&dir.path().join("new").join("agent.sock")).is_ok());
+        assert_eq!(fs::metadata(dir.path().join("new")).unwrap().mode() & 0o777, 0o700);
+
+        // a symlinked directory is not followed
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(&sock_dir, &link).unwrap();
+        assert!(SshAgent::default().listen(&link.join("agent.sock")).is_err());
+    }
+}
@@ -5,8 +5,8 @@ use rand::{rngs::OsRng, RngCore};
 use argon2::{Algorithm, Version, Params, Argon2};
 use chacha20poly1305::{aead::{Aead, KeyInit}, ChaCha20Poly1305, Key, Nonce};
 
+use crate::gen;
 use crate::store::{self, Entry, EntriesFile};
-use std::io::Write;
 
 const ARGON_MEM_DESKTOP: u32 = 19456;
 const ARGON_MEM_MOBILE: u32 = 8192;
@@ -116,16 +116,39 @@ fn decrypt_entries(data: &[u8], passphrase: Option<String>) -> Result<Vec<Entry>
     Ok(parsed.entries)
 }
 
+// Refuse the whole import rather than store entries that would later fail to generate
+// (or, before MethodSpec, silently generate a different password).
+fn check_methods(entries: &[Entry]) -> Result<(), String> {
+    for e in entries.iter() {
+        gen::check(&e.method_id, &e.gen_options()).map_err(|err| format!("entry \"{}\": {}", e.label, err))?;
+    }
+    Ok(())
+}
+
 pub fn export_to_path(path: &str, passphrase: Option<String>) -> Result<(), String> {
     let entries = store::list();
     let data = encrypt_entries(entries, passphrase)?;
     fs::write(path, data).map_err(|e| e.to_string())
 }
 
-pub fn import_from_path(path: &str, passphrase: Option<String>, overwrite: bool) -> Result<usize, String> {
+/// Outcome of an import. Entries filed under a fingerprint that names no master here are kept,
+/// not dropped: ones from a backup made before keyed fingerprints move to their master when it
+/// is next unlocked (`crypto::adopt_legacy_entries`), and `unmatched` lets the UI say so.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct ImportSummary { pub imported: usize, pub unmatched: usize }
+
+fn store_imported(entries: Vec<Entry>, overwrite: bool) -> ImportSummary {
+    let local: HashSet<String> = crate::crypto::list_master_fingerprints().into_iter().collect();
+    let unmatched = entries.iter().filter(|e| e.fingerprint.as_ref().is_some_and(|fp| !local.contains(fp))).count();
+    let imported = if overwrite { store::replace_all(entries) } else { store::merge(entries) };
+    ImportSummary { imported, unmatched }
+}
+
+pub fn import_from_path(path: &str, passphrase: Option<String>, overwrite: bool) -> Result<ImportSummary, String> {
     let data = fs::read(path).map_err(|e| e.to_string())?;
     let entries = decrypt_entries(&data, passphrase)?;
-    if overwrite { Ok(store::replace_all(entries)) } else { Ok(store::merge(entries)) }
+    check_methods(&entries)?;
+    Ok(store_imported(entries, overwrite))
 }
 
 pub fn preview_backup(path: &str, passphrase: Option<String>) -> Result<CsvPreview, String> {
@@ -149,6 +172,7 @@ pub fn import_with_mapping(path: &str, passphrase: Option<String>, mapping: Vec<
     let data = fs::read(path).map_err(|e| e.to_string())?;
     let entries = decrypt_entries(&data, passphrase)?;
     let mapped = map_entries(entries, mapping);
+    check_methods(&mapped)?;
     if overwrite { Ok(store::replace_all(mapped)) } else { Ok(store::merge(mapped)) }
 }
 
@@ -169,31 +193,129 @@ fn map_entries(entries: Vec<Entry>, mapping: Vec<CsvMapping>) -> Vec<Entry> {
     out
 }
 
-pub fn import_entries_payload(entries: Vec<Entry>, overwrite: bool) -> Result<usize, String> {
-    let allowed: HashSet<String> = crate::crypto::list_master_fingerprints().into_iter().collect();
-    let filtered: Vec<Entry> = entries
-        .into_iter()
-        .filter(|e| e.fingerprint.as_ref().map(|fp| allowed.contains(fp)).unwrap_or(false))
-        .collect();
-    if overwrite { Ok(store::replace_all(filtered)) } else { Ok(store::merge(filtered)) }
+pub fn import_entries_payload(entries: Vec<Entry>, overwrite: bool) -> Result<ImportSummary, String> {
+    check_methods(&entries)?;
+    Ok(store_imported(entries, overwrite))
 }
 
 // --- CSV support ---
+// Columns are looked up by header name, so files written before a column existed still
+// import. Settings that change the derived password are JSON cells.
+const CSV_COLUMNS: [&str; 12] = [
+    "fingerprint", "label", "postfix", "method_id", "created_at", "id", "policy", "counter", "previous_counter", "lesspass",
+    "spectre", "security_questions",
+];
+
+fn csv_cell(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) { format!("\"{}\"", s.replace('"', "\"\"")) } else { s.to_string() }
+}
+
+fn json_cell<T: Serialize>(value: Option<&T>) -> Result<String, String> {
+    value.map_or(Ok(String::new()), |v| serde_json::to_string(v).map_err(|e| e.to_string()))
+}
+
+fn parse_json_cell<T: serde::de::DeserializeOwned>(cell: &str, label: &str, column: &str) -> Result<Option<T>, String> {
+    if cell.trim().is_empty() { return Ok(None); }
+    serde_json::from_str(cell).map(Some).map_err(|e| format!("entry \"{}\": bad {} column: {}", label, column, e))
+}
+
+// RFC 4180 records: quoted cells may hold commas, doubled quotes and line breaks
+fn parse_csv(data: &str) -> Vec<Vec<String>> {
+    let mut rows: Vec<Vec<String>> = vec![];
+    let mut row: Vec<String> = vec![];
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = data.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => { chars.next(); cell.push('"'); }
+            (true, '"') => quoted = false,
+            (false, '"') => quoted = true,
+            (false, ',') => row.push(std::mem::take(&mut cell)),
+            (false, '\n') => { row.push(std::mem::take(&mut cell)); rows.push(std::mem::take(&mut row)); }
+            (false, '\r') => {}
+            (_, c) => cell.push(c),
+        }
+    }
+    if !cell.is_empty() || !row.is_empty() { row.push(cell); rows.push(row); }
+    rows.retain(|r| r.iter().any(|c| !c.trim().is_empty()));
+    rows
+}
+
+// Header plus the rows that carry at least the original six columns
+fn read_csv(data: &str) -> (Vec<String>, Vec<Vec<String>>) {
+    let mut rows = parse_csv(data).into_iter();
+    let header = rows.next().unwrap_or_default().into_iter().map(|h| h.trim().to_string()).collect();
+    (header, rows.filter(|r| r.len() >= 6).collect())
+}
+
+fn entries_to_csv(entries: &[Entry]) -> Result<String, String> {
+    let mut out = CSV_COLUMNS.join(",");
+    out.push('\n');
+    for e in entries.iter() {
+        // a sealed OTP secret is bound to its entry and master; only the encrypted backup keeps it
+        if e.otp.is_some() {
+            return Err(format!("entry \"{}\" has a one-time code secret, which CSV cannot carry; use an encrypted backup", e.label));
+        }
+        let cells = [
+            e.fingerprint.clone().unwrap_or_default(),
+            e.label.clone(),
+            e.postfix.clone(),
+            e.method_id.clone(),
+            e.created_at.to_string(),
+            e.id.clone(),
+            json_cell(e.policy.as_ref())?,
+            e.counter.to_string(),
+            e.previous_counter.map_or(String::new(), |c| c.to_string()),
+            json_cell(e.lesspass.as_ref())?,
+            json_cell(e.spectre.as_ref())?,
+            json_cell((!e.security_questions.is_empty()).then_some(&e.security_questions))?,
+        ];
+        out.push_str(&cells.iter().map(|c| csv_cell(c)).collect::<Vec<_>>().join(","));
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+fn entries_from_csv(data: &str, mapping: Vec<CsvMapping>) -> Result<Vec<Entry>, String> {
+    let map: HashMap<String, Option<String>> = mapping.into_iter().map(|m| (m.from, m.to)).collect();
+    let (header, rows) = read_csv(data);
+    let mut list: Vec<Entry> = vec![];
+    for row in rows.iter() {
+        let cell = |name: &str| header.iter().position(|h| h == name).and_then(|i| row.get(i)).map_or("", String::as_str);
+        let target = map.get(cell("fingerprint").trim()).cloned().unwrap_or(None);
+        if target.is_none() { continue; }
+        let label = cell("label").to_string();
+        let counter = |name: &str| -> Result<Option<u32>, String> {
+            let c = cell(name).trim();
+            if c.is_empty() { return Ok(None); }
+            c.parse().map(Some).map_err(|_| format!("entry \"{}\": bad {} column", label, name))
+        };
+        let e = Entry {
+            id: cell("id").to_string(),
+            postfix: cell("postfix").to_string(),
+            method_id: cell("method_id").to_string(),
+            created_at: cell("created_at").parse().unwrap_or(0),
+            order: 0,
+            fingerprint: target,
+            policy: parse_json_cell(cell("policy"), &label, "policy")?,
+            counter: counter("counter")?.unwrap_or(0),
+            previous_counter: counter("previous_counter")?,
+            lesspass: parse_json_cell(cell("lesspass"), &label, "lesspass")?,
+            spectre: parse_json_cell(cell("spectre"), &label, "spectre")?,
+            ssh_agent: false,
+            otp: None,
+            security_questions: parse_json_cell(cell("security_questions"), &label, "security_questions")?.unwrap_or_default(),
+            label,
+        };
+        list.push(e);
+    }
+    Ok(list)
+}
+
 pub fn export_to_csv(path: &str) -> Result<(), String> {
     let entries = store::list_for_fingerprint(&None);
-    let mut wtr = Vec::new();
-    // header
-    writeln!(&mut wtr, "fingerprint,label,postfix,method_id,created_at,id").map_err(|e| e.to_string())?;
-    for e in entries.into_iter() {
-        let fp = e.fingerprint.clone().unwrap_or_default();
-        let safe = |s: &str| s.replace('"', "\"");
-        let line = format!(
-            "{}\n",
-            [fp, safe(&e.label), safe(&e.postfix), e.method_id.clone(), e.created_at.to_string(), e.id.clone()].join(",")
-        );
-        wtr.extend_from_slice(line.as_bytes());
-    }
-    std::fs::write(path, wtr).map_err(|e| e.to_string())
+    std::fs::write(path, entries_to_csv(&entries)?).map_err(|e| e.to_string())
 }
 
 #[derive(Serialize)]
@@ -201,12 +323,11 @@ pub struct CsvPreview { pub fingerprints: Vec<(String, usize)>, }
 
 pub fn preview_csv(path: &str) -> Result<CsvPreview, String> {
     let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let (header, rows) = read_csv(&data);
+    let col = header.iter().position(|h| h == "fingerprint").unwrap_or(0);
     let mut counts: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
-    for (i, line) in data.lines().enumerate() {
-        if i == 0 { continue; }
-        let parts: Vec<&str> = line.split(',').collect();
-        if parts.len() < 6 { continue; }
-        let fp = parts[0].trim().to_string();
+    for row in rows.iter() {
+        let fp = row.get(col).map_or("", |c| c.trim()).to_string();
         *counts.entry(fp).or_insert(0) += 1;
     }
     let mut v: Vec<(String, usize)> = counts.into_iter().collect();
@@ -218,24 +339,9 @@ pub fn preview_csv(path: &str) -> Result<CsvPreview, String> {
 pub struct CsvMapping { pub from: String, pub to: Option<String> }
 
 pub fn import_csv_apply(path: &str, mapping: Vec<CsvMapping>, overwrite: bool) -> Result<usize, String> {
-    let map: std::collections::HashMap<String, Option<String>> = mapping.into_iter().map(|m| (m.from, m.to)).collect();
     let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
-    let mut list: Vec<Entry> = vec![];
-    for (i, line) in data.lines().enumerate() {
-        if i == 0 { continue; }
-        let parts: Vec<&str> = line.split(',').collect();
-        if parts.len() < 6 { continue; }
-        let from_fp = parts[0].trim().to_string();
-        let target = map.get(&from_fp).cloned().unwrap_or(None);
-        if target.is_none() { continue; }
-        let label = parts[1].to_string();
-        let postfix = parts[2].to_string();
-        let method_id = parts[3].to_string();
-        let created_at: u64 = parts[4].parse().unwrap_or(0);
-        let id = parts[5].to_string();
-        let e = Entry { id, label, postfix, method_id, created_at, order: 0, fingerprint: target.clone() };
-        list.push(e);
-    }
+    let list = entries_from_csv(&data, mapping)?;
+    check_methods(&list)?;
     if overwrite { Ok(store::replace_all(list)) } else { Ok(store::merge(list)) }
 }
 
@@ -255,6 +361,14 @@ mod tests {
                 created_at: 1,
                 order: 0,
                 fingerprint: Some("fp1".into()),
+                policy: None,
+                counter: 0,
+                previous_counter: None,
+                lesspass: None,
+                spectre: None,
+                ssh_agent: false,
+                otp: None,
+                security_questions: Vec::new(),
             }
         ]
     }
@@ -295,12 +409,31 @@ mod tests {
         assert!(decrypt_entries(&blob, None).is_err());
     }
 
+    #[test]
+    fn import_rejects_unknown_methods() {
+        assert!(check_methods(&sample_entries()).is_ok());
+        let mut bad = sample_entries();
+        bad[0].method_id = "len36_strnog".into();
+        let err = check_methods(&bad).unwrap_err();
+        assert!(err.contains("Example") && err.contains("len36_strnog"));
+        let mut legacy_policy = sample_entries();
+        legacy_policy[0].method_id = "legacy_v1".into();
+        legacy_policy[0].policy = Some(gen::Policy::default());
+        assert!(check_methods(&legacy_policy).is_err());
+        let mut len_counter = sample_entries();
+        len_counter[0].counter = 2;
+        assert!(check_methods(&len_counter).is_err());
+    }
+
     fn entries_file_bytes(entries: Vec<Entry>) -> Vec<u8> {
         serde_json::to_vec(&EntriesFile { entries }).unwrap()
     }
 
     #[test]
     fn csv_roundtrip_mapping() {
+        let _lock = crate::paths::TEST_DATA_DIR.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = tempfile::tempdir().unwrap();
+        std::env::set_var("SAFORIA_DATA_DIR", dir.path());
         let tmp = tempfile::NamedTempFile::new().unwrap();
         let entries = sample_entries();
         // write csv
@@ -315,5 +448,88 @@ mod tests {
         // apply with mapping
         let mapping = vec![CsvMapping { from: "fp1".into(), to: Some("fp1".into()) }];
         let _ = import_csv_apply(tmp.path().to_str().unwrap(), mapping, true).unwrap_or(0);
+        std::env::remove_var("SAFORIA_DATA_DIR");
+    }
+
+    #[test]
+    fn imports_pre_migration_backups() {
+        let _lock = crate::paths::TEST_DATA_DIR.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = tempfile::tempdir().unwrap();
+        std::env::set_var("SAFORIA_DATA_DIR", dir.path());
+        let fp = crate::crypto::save_master("viewer", "test").unwrap();
+        // a backup made while masters were still filed under MD5("test")
+        let mut entries = sample_entries();
+        entries[0].fingerprint = Some("098f6bcd4621d373cade4e832627b4f6".into());
+        let backup = dir.path().join("old.json");
+        fs::write(&backup, encrypt_entries(entries, None).unwrap()).unwrap();
+
+        let summary = import_from_path(backup.to_str().unwrap(), None, false).unwrap();
+        assert_eq!(summary, ImportSummary { imported: 1, unmatched: 1 });
+        assert!(store::list_for_fingerprint(&Some(fp.clone())).is_empty());
+        assert_eq!(crate::crypto::adopt_legacy_entries("test", &fp).unwrap(), 1);
+        let listed = store::list_for_fingerprint(&Some(fp.clone()));
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].fingerprint.as_deref(), Some(fp.as_str()));
+        std::env::remove_var("SAFORIA_DATA_DIR");
+    }
+
+    #[test]
+    fn csv_roundtrip_keeps_generation_settings() {
+        let mut entries = sample_entries();
+        entries[0].label = "Example, \"Inc\"".into();
+        entries[0].method_id = "v3_len20_strong".into();
+        entries[0].policy = Some(gen::Policy { min_len: Some(24), require_symbol: true, symbols: Some("-_".into()), ..Default::default() });
+        entries[0].counter = 3;
+        entries[0].previous_counter = Some(2);
+        let csv = entries_to_csv(&entries).unwrap();
+        let mapping = vec![CsvMapping { from: "fp1".into(), to: Some("fp2".into()) }];
+        let back = entries_from_csv(&csv, mapping).unwrap();
+        assert_eq!(back.len(), 1);
+        assert_eq!(back[0].label, entries[0].label);
+        assert_eq!(back[0].fingerprint.as_deref(), Some("fp2"));
+        assert_eq!(back[0].policy, entries[0].policy);
+        assert_eq!((back[0].counter, back[0].previous_counter), (3, Some(2)));
+        assert!(back[0].security_questions.is_empty());
+        check_methods(&back).unwrap();
+
+        let mut questions = sample_entries();
+        questions[0].security_questions = vec![
+            gen::SecurityQuestion { question: "First pet, \"name\"?".into(), style: gen::AnswerStyle::Pronounceable },
+            gen::SecurityQuestion { question: "Street".into(), style: gen::AnswerStyle::Words },
+        ];
+        let back = entries_from_csv(&entries_to_csv(&questions).unwrap(), vec![CsvMapping { from: "fp1".into(), to: Some("fp1".into()) }]).unwrap();
+        assert_eq!(back[0].security_questions, questions[0].security_questions);
+
+        let mut lesspass = sample_entries();
+        lesspass[0].method_id = "lesspass".into();
+        lesspass[0].lesspass = Some(gen::LessPassProfile { login: "contact@example.org".into(), length: 14, counter: 2, symbols: false, ..Default::default() });
+        let back = entries_from_csv(&entries_to_csv(&lesspass).unwrap(), vec![CsvMapping { from: "fp1".into(), to: Some("fp1".into()) }]).unwrap();
+        assert_eq!(back[0].lesspass, lesspass[0].lesspass);
+        check_methods(&back).unwrap();
+
+        let mut spectre = sample_entries();
+        spectre[0].method_id = "spectre".into();
+        spectre[0].spectre = Some(gen::SpectreProfile { full_name: "Robert Lee Mitchell".into(), counter: 2, template: gen::SpectreTemplate::Basic });
+        let back = entries_from_csv(&entries_to_csv(&spectre).unwrap(), vec![CsvMapping { from: "fp1".into(), to: Some("fp1".into()) }]).unwrap();
+        assert_eq!(back[0].spectre, spectre[0].spectre);
+        check_methods(&back).unwrap();
+    }
+
+    #[test]
+    fn csv_export_refuses_otp_secrets() {
+        let mut entries = sample_entries();
+        entries[0].otp = Some(crate::otp::SealedOtp { nonce_b64: "AA".into(), ciphertext_b64: "AA".into() });
+        let err = entries_to_csv(&entries).unwrap_err();
+        assert!(err.contains("Example") && err.contains("one-time code"));
+    }
+
+    #[test]
+    fn csv_without_new_columns_still_imports() {
+        let csv = "fingerprint,label,postfix,method_id,created_at,id\nfp1,Example,example.com,len36_strong,1,1\n";
+        let back = entries_from_csv(csv, vec![CsvMapping { from: "fp1".into(), to: Some("fp1".into()) }]).unwrap();
+        assert_eq!(back.len(), 1);
+        assert_eq!(back[0].postfix, "example.com");
+        assert!(back[0].policy.is_none());
+        assert_eq!((back[0].counter, back[0].previous_counter), (0, None));
     }
 }
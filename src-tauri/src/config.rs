@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::{fs, path::PathBuf};
-use crate::paths::{app_data_dir, ensure_dir};
+use crate::paths::{app_data_dir, ensure_dir, write_atomic};
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Prefs {
@@ -28,6 +28,12 @@ pub struct Prefs {
     pub hold_only_reveal: bool,
     #[serde(default)]
     pub clear_clipboard_on_blur: bool,
+    /// Email template such as `me+{alias}@example.com`; saved entries get an address from it
+    #[serde(default)]
+    pub alias_template: Option<String>,
+    /// Fingerprints whose master file has been written as v4; an older file for them is refused
+    #[serde(default)]
+    pub authenticated_masters: Vec<String>,
 }
 
 fn prefs_path() -> PathBuf {
@@ -67,10 +73,12 @@ pub fn read_prefs() -> Prefs {
         copy_on_console_generate: false,
         hold_only_reveal: false,
         clear_clipboard_on_blur: false,
+        alias_template: None,
+        authenticated_masters: vec![],
     }
 }
 
 pub fn write_prefs(p: &Prefs) -> Result<(), std::io::Error> {
     let path = prefs_path();
-    fs::write(path, serde_json::to_string_pretty(p).unwrap())
+    write_atomic(&path, serde_json::to_string_pretty(p).unwrap().as_bytes())
 }
@@ -1,15 +1,40 @@
 use argon2::{Argon2, Params, Algorithm, Version};
-use aes_gcm::{aead::{Aead, KeyInit}, Aes256Gcm, Key, Nonce};
+use aes_gcm::{aead::{Aead, KeyInit, Payload}, Aes256Gcm, Key, Nonce};
 use chacha20poly1305::{ChaCha20Poly1305, Key as ChKey, Nonce as ChNonce};
 use rand::{rngs::OsRng, RngCore};
 use serde::{Deserialize, Serialize};
-use zeroize::Zeroize;
-use std::{fs, path::PathBuf};
+use zeroize::Zeroizing;
+use std::{fs, path::PathBuf, time::{Duration, Instant}};
 use base64::{engine::general_purpose, Engine as _};
 use thiserror::Error;
 use md5;
 
-use crate::paths::masters_dir;
+use crate::paths::{install_salt_file, masters_dir, write_atomic};
+use crate::{config, gen, store};
+
+// Master fingerprints: Argon2id over the master, salted per install. Fixed cost on every
+// platform so an install's fingerprints never change; each guessed master costs a full run.
+const FINGERPRINT_TAG: &[u8] = b"saforia/fingerprint";
+const FINGERPRINT_MEM_KIB: u32 = 19456;
+const FINGERPRINT_ITERATIONS: u32 = 2;
+const FINGERPRINT_LEN: usize = 16;
+const INSTALL_SALT_LEN: usize = 32;
+
+// Argon2id cost of new key slots; mobile trades memory for RAM and battery
+const ARGON_MEM_DESKTOP: u32 = 19456;
+const ARGON_MEM_MOBILE: u32 = 8192;
+const ARGON_ITERATIONS: u32 = 2;
+const ARGON_PARALLELISM: u32 = 1;
+// Accepted from a file at most, so a crafted one can't demand gigabytes or minutes per try
+const ARGON_MEM_MAX: u32 = 2 * 1024 * 1024;
+const ARGON_ITERATIONS_MAX: u32 = 64;
+const ARGON_PARALLELISM_MAX: u32 = 16;
+// Calibration grows memory up to this, then iterations; stays openable on the other platform
+#[cfg(any(target_os = "android", target_os = "ios"))]
+const CALIBRATE_MEM_MAX: u32 = 65536;
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+const CALIBRATE_MEM_MAX: u32 = 262144;
+pub const DEFAULT_UNLOCK_TARGET: Duration = Duration::from_millis(1000);
 
 #[derive(Debug, Error)]
 pub enum CryptoError {
@@ -17,6 +42,11 @@ pub enum CryptoError {
     #[error("json: {0}")] Json(#[from] serde_json::Error),
     #[error("decryption failed")] Decryption,
     #[error("master not found")] NotFound,
+    #[error("key slot not found")] SlotNotFound,
+    #[error("{0}")] Slot(String),
+    #[error("key derivation: {0}")] Kdf(String),
+    #[error("unsupported master file version {0}")] UnsupportedVersion(u32),
+    #[error("master file has been tampered with")] Tampered,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -27,18 +57,152 @@ pub struct MasterFile {
     pub ciphertext_b64: String,
 }
 
-fn derive_key(viewer_password: &str, salt: &[u8]) -> [u8; 32] {
-    // Memory and time cost balanced for desktop and mobile
+/// Argon2id cost recorded with every key slot; field names follow `backup::EncFileV2`. Slots
+/// written before they were recorded used this platform's defaults.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KdfParams {
+    #[serde(default = "default_mem")]
+    pub mem_kib: u32,
+    #[serde(default = "default_iterations")]
+    pub iterations: u32,
+    #[serde(default = "default_parallelism")]
+    pub parallelism: u32,
+}
+
+fn default_mem() -> u32 {
+    if cfg!(any(target_os = "android", target_os = "ios")) { ARGON_MEM_MOBILE } else { ARGON_MEM_DESKTOP }
+}
+fn default_iterations() -> u32 { ARGON_ITERATIONS }
+fn default_parallelism() -> u32 { ARGON_PARALLELISM }
+fn default_kdf() -> String { "argon2id".into() }
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        KdfParams { mem_kib: default_mem(), iterations: ARGON_ITERATIONS, parallelism: ARGON_PARALLELISM }
+    }
+}
+
+impl KdfParams {
+    fn check(&self) -> Result<(), CryptoError> {
+        let ok = (ARGON_MEM_MOBILE..=ARGON_MEM_MAX).contains(&self.mem_kib)
+            && (1..=ARGON_ITERATIONS_MAX).contains(&self.iterations)
+            && (1..=ARGON_PARALLELISM_MAX).contains(&self.parallelism);
+        if ok { Ok(()) } else { Err(CryptoError::Kdf(format!("parameters out of range: {:?}", self))) }
+    }
+
+    /// At least as much memory and as much total work as `other`
+    fn at_least(&self, other: &KdfParams) -> bool {
+        self.mem_kib >= other.mem_kib
+            && self.mem_kib as u64 * self.iterations as u64 >= other.mem_kib as u64 * other.iterations as u64
+    }
+}
+
+fn derive_key(secret: &str, salt: &[u8], kdf: &KdfParams) -> Result<[u8; 32], CryptoError> {
+    kdf.check()?;
+    let params = Params::new(kdf.mem_kib, kdf.iterations, kdf.parallelism, Some(32)).map_err(|e| CryptoError::Kdf(e.to_string()))?;
     let mut out = [0u8; 32];
-    // Reduce memory on mobile targets
-    #[cfg(any(target_os = "android", target_os = "ios"))]
-    let params = Params::new(8192, 2, 1, Some(32)).unwrap();
-    #[cfg(not(any(target_os = "android", target_os = "ios")))]
-    let params = Params::new(19456, 2, 1, Some(32)).unwrap();
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(secret.as_bytes(), salt, &mut out)
+        .map_err(|e| CryptoError::Kdf(e.to_string()))?;
+    Ok(out)
+}
 
-    let alg = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
-    alg.hash_password_into(viewer_password.as_bytes(), salt, &mut out).unwrap();
-    out
+/// Argon2id parameters taking about `target` per key derivation on this device: memory doubles
+/// from the default up to `CALIBRATE_MEM_MAX`, then iterations fill the rest. Never below the
+/// default, so slow devices get the default.
+pub fn calibrate_kdf(target: Duration) -> Result<KdfParams, CryptoError> {
+    let time = |kdf: &KdfParams| -> Result<Duration, CryptoError> {
+        let start = Instant::now();
+        derive_key("calibration", &[0u8; 16], kdf)?;
+        Ok(start.elapsed())
+    };
+    let mut kdf = KdfParams::default();
+    let mut elapsed = time(&kdf)?;
+    while kdf.mem_kib * 2 <= CALIBRATE_MEM_MAX && elapsed * 2 <= target {
+        kdf.mem_kib *= 2;
+        elapsed = time(&kdf)?;
+    }
+    let per_iteration = elapsed.as_secs_f64() / kdf.iterations as f64;
+    if per_iteration > 0.0 {
+        kdf.iterations = ((target.as_secs_f64() / per_iteration) as u32).clamp(kdf.iterations, ARGON_ITERATIONS_MAX);
+    }
+    Ok(kdf)
+}
+
+fn install_salt() -> Result<Vec<u8>, CryptoError> {
+    let path = install_salt_file();
+    match fs::read(&path) {
+        Ok(salt) if salt.len() == INSTALL_SALT_LEN => Ok(salt),
+        // never silently replace it: every fingerprint on this install depends on it
+        Ok(_) => Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "install salt is corrupted").into()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let mut salt = vec![0u8; INSTALL_SALT_LEN];
+            OsRng.fill_bytes(&mut salt);
+            write_atomic(&path, &salt)?;
+            Ok(salt)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn keyed_fingerprint(master: &str, install_salt: &[u8]) -> String {
+    let salt = [FINGERPRINT_TAG, install_salt].concat();
+    let params = Params::new(FINGERPRINT_MEM_KIB, FINGERPRINT_ITERATIONS, 1, Some(FINGERPRINT_LEN)).unwrap();
+    let mut out = [0u8; FINGERPRINT_LEN];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params).hash_password_into(master.as_bytes(), &salt, &mut out).unwrap();
+    out.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Identifier of a master on this install: names its file under `masters/` and binds entries.
+/// Without the install salt and an Argon2id run per guess it reveals nothing about the master.
+pub fn fingerprint(master: &str) -> Result<String, CryptoError> {
+    Ok(keyed_fingerprint(master, &install_salt()?))
+}
+
+fn legacy_fingerprint(master: &str) -> String {
+    format!("{:x}", md5::compute(master.as_bytes()))
+}
+
+/// Whether `fp` is the plaintext MD5 of `master` that older versions used as its identifier
+pub fn is_legacy_fingerprint(master: &str, fp: &str) -> bool {
+    legacy_fingerprint(master) == fp
+}
+
+/// Rebinds entries still filed under the legacy fingerprint of `master` to its keyed one `fp`:
+/// imported from a backup made before the migration, they match no master file.
+pub fn adopt_legacy_entries(master: &str, fp: &str) -> Result<usize, CryptoError> {
+    Ok(store::rebind_fingerprint(&legacy_fingerprint(master), fp)?)
+}
+
+/// Refiles a master kept under its legacy MD5 fingerprint under the keyed one and rebinds
+/// saved entries and the active master. Each step is an atomic write and the old file goes
+/// last, once the keyed one has given back the master, so an interrupted migration simply
+/// runs again on the next unlock.
+pub fn migrate_legacy_fingerprint(legacy: &str, viewer_password: &str, master: &str) -> Result<String, CryptoError> {
+    let fp = fingerprint(master)?;
+    // a file already there should hold the same master (set up again, or an earlier attempt);
+    // otherwise it is sealed again, as the fingerprint is part of its header
+    let keep_legacy = if master_file_path_for(&fp).exists() {
+        match unlock(viewer_password, &fp, &read_master_file(&fp)?) {
+            Ok(kept) if kept.master.as_str() == master => false,
+            Ok(_) => return Err(CryptoError::Tampered),
+            // set up again with another viewer password: both files stay
+            Err(CryptoError::Decryption) => true,
+            Err(e) => return Err(e),
+        }
+    } else {
+        let u = unlock(viewer_password, legacy, &read_master_file(legacy)?)?;
+        replace_master_file(&fp, &u, viewer_password)?;
+        false
+    };
+    store::rebind_fingerprint(legacy, &fp)?;
+    let mut prefs = config::read_prefs();
+    if prefs.active_fingerprint.as_deref() == Some(legacy) {
+        prefs.active_fingerprint = Some(fp.clone());
+        config::write_prefs(&prefs)?;
+    }
+    if !keep_legacy { fs::remove_file(master_file_path_for(legacy))?; }
+    Ok(fp)
 }
 
 pub fn master_file_path_for(fp: &str) -> PathBuf {
@@ -47,116 +211,648 @@ pub fn master_file_path_for(fp: &str) -> PathBuf {
     dir
 }
 
-pub fn save_master(viewer_password: &str, master_password: &str) -> Result<String, CryptoError> {
-    let _ = masters_dir();
+/// One way into a v3/v4 master file: the data key wrapped under a key derived from a viewer
+/// password, or from the recovery code when `recovery` is set. Labels are stored in the clear.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct KeySlot {
+    pub label: String,
+    #[serde(default)]
+    pub recovery: bool,
+    #[serde(default = "default_kdf")]
+    pub kdf: String,
+    #[serde(flatten)]
+    pub params: KdfParams,
+    pub salt_b64: String,
+    pub nonce_b64: String,
+    pub wrapped_key_b64: String,
+}
+
+/// v3 and v4: the master is encrypted once under a random data key that every slot wraps. v4
+/// also authenticates the version, the fingerprint and every slot (see `header_aad`).
+#[derive(Serialize, Deserialize)]
+struct KeySlotFile {
+    version: u32,
+    nonce_b64: String,
+    ciphertext_b64: String,
+    slots: Vec<KeySlot>,
+}
+
+#[derive(Deserialize)]
+struct Versioned { version: u32 }
+
+#[derive(Serialize)]
+pub struct SlotInfo {
+    pub label: String,
+    pub recovery: bool,
+    #[serde(flatten)]
+    pub params: KdfParams,
+}
+
+const MASTER_FILE_VERSION: u32 = 4;
+const HEADER_TAG: &[u8] = b"saforia/master-file";
+
+/// Label of the slot created at setup and of the one a v1/v2 file becomes
+pub const DEFAULT_SLOT: &str = "default";
+pub const RECOVERY_SLOT: &str = "recovery";
+// Crockford base32; 32 symbols are 160 bits
+const RECOVERY_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const RECOVERY_LEN: usize = 32;
+
+fn b64(bytes: &[u8]) -> String { general_purpose::STANDARD_NO_PAD.encode(bytes) }
 
+fn unb64(s: &str) -> Result<Vec<u8>, CryptoError> {
+    general_purpose::STANDARD_NO_PAD.decode(s).map_err(|_| CryptoError::Decryption)
+}
+
+fn aes_encrypt(key: &[u8; 32], plaintext: &[u8], aad: &[u8]) -> Result<(String, String), CryptoError> {
+    let mut nonce = [0u8; 12];
+    OsRng.fill_bytes(&mut nonce);
+    let ciphertext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key))
+        .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad })
+        .map_err(|_| CryptoError::Decryption)?;
+    Ok((b64(&nonce), b64(&ciphertext)))
+}
+
+fn aes_decrypt(key: &[u8; 32], nonce_b64: &str, ciphertext_b64: &str, aad: &[u8]) -> Result<Zeroizing<Vec<u8>>, CryptoError> {
+    let nonce = unb64(nonce_b64)?;
+    if nonce.len() != 12 { return Err(CryptoError::Decryption); }
+    Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key))
+        .decrypt(Nonce::from_slice(&nonce), Payload { msg: unb64(ciphertext_b64)?.as_ref(), aad })
+        .map(Zeroizing::new)
+        .map_err(|_| CryptoError::Decryption)
+}
+
+fn wrap_slot(label: &str, recovery: bool, secret: &str, data_key: &[u8; 32], params: KdfParams) -> Result<KeySlot, CryptoError> {
     let mut salt = [0u8; 16];
     OsRng.fill_bytes(&mut salt);
-    let key_bytes = derive_key(viewer_password, &salt);
-    let key = Key::<Aes256Gcm>::from_slice(&key_bytes);
-    let cipher = Aes256Gcm::new(key);
+    let kek = Zeroizing::new(derive_key(secret, &salt, &params)?);
+    let (nonce_b64, wrapped_key_b64) = aes_encrypt(&kek, data_key, &[])?;
+    Ok(KeySlot { label: label.to_string(), recovery, kdf: default_kdf(), params, salt_b64: b64(&salt), nonce_b64, wrapped_key_b64 })
+}
 
-    let mut nonce_bytes = [0u8; 12];
-    OsRng.fill_bytes(&mut nonce_bytes);
-    let nonce = Nonce::from_slice(&nonce_bytes);
+fn unwrap_slot(secret: &str, slot: &KeySlot) -> Result<Zeroizing<[u8; 32]>, CryptoError> {
+    if slot.kdf != "argon2id" { return Err(CryptoError::Kdf(format!("unsupported function {:?}", slot.kdf))); }
+    let kek = Zeroizing::new(derive_key(secret, &unb64(&slot.salt_b64)?, &slot.params)?);
+    let key = aes_decrypt(&kek, &slot.nonce_b64, &slot.wrapped_key_b64, &[])?;
+    let key: [u8; 32] = key.as_slice().try_into().map_err(|_| CryptoError::Decryption)?;
+    Ok(Zeroizing::new(key))
+}
 
-    let ciphertext = cipher
-        .encrypt(nonce, master_password.as_bytes())
-        .map_err(|_| CryptoError::Decryption)?;
+/// Printable recovery code: 32 Crockford base32 symbols (160 bits) in groups of four
+fn new_recovery_code() -> Zeroizing<String> {
+    let mut code = Zeroizing::new(String::with_capacity(RECOVERY_LEN + RECOVERY_LEN / 4));
+    let mut bytes = Zeroizing::new([0u8; RECOVERY_LEN]);
+    OsRng.fill_bytes(bytes.as_mut());
+    for (i, b) in bytes.iter().enumerate() {
+        if i > 0 && i % 4 == 0 { code.push('-'); }
+        code.push(RECOVERY_ALPHABET[(b & 31) as usize] as char);
+    }
+    code
+}
+
+/// The canonical symbols of a typed recovery code (case, dashes and spaces ignored, O/I/L read
+/// as 0/1), or `None` if it can't be one; viewer passwords then skip the recovery slot's KDF.
+fn normalize_recovery_code(input: &str) -> Option<Zeroizing<String>> {
+    let code: Zeroizing<String> = Zeroizing::new(input.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .map(|c| match c.to_ascii_uppercase() { 'O' => '0', 'I' | 'L' => '1', c => c })
+        .collect());
+    (code.len() == RECOVERY_LEN && code.bytes().all(|b| RECOVERY_ALPHABET.contains(&b))).then_some(code)
+}
+
+fn new_data_key() -> Zeroizing<[u8; 32]> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    OsRng.fill_bytes(key.as_mut());
+    key
+}
+
+// Associated data of a v4 file's master ciphertext: the version, the fingerprint the file is
+// named after and every slot as stored. Slot wraps carry none, so a v3 file can be upgraded
+// with one slot's secret; the data key is what every slot must agree on.
+fn header_aad(version: u32, fp: &str, slots: &[KeySlot]) -> Vec<u8> {
+    let (version, count) = (version.to_be_bytes(), (slots.len() as u32).to_be_bytes());
+    let mut fields: Vec<Vec<u8>> = vec![HEADER_TAG.to_vec(), version.to_vec(), fp.as_bytes().to_vec(), count.to_vec()];
+    for s in slots {
+        fields.extend([
+            s.label.as_bytes().to_vec(), vec![s.recovery as u8], s.kdf.as_bytes().to_vec(),
+            s.params.mem_kib.to_be_bytes().to_vec(), s.params.iterations.to_be_bytes().to_vec(), s.params.parallelism.to_be_bytes().to_vec(),
+            s.salt_b64.as_bytes().to_vec(), s.nonce_b64.as_bytes().to_vec(), s.wrapped_key_b64.as_bytes().to_vec(),
+        ]);
+    }
+    gen::frame(&fields.iter().map(Vec::as_slice).collect::<Vec<_>>())
+}
+
+// A fresh v4 file; the master is re-encrypted whenever the slots change, as they are part of its AAD
+fn seal(fp: &str, master_password: &str, data_key: &[u8; 32], slots: Vec<KeySlot>) -> Result<KeySlotFile, CryptoError> {
+    let aad = header_aad(MASTER_FILE_VERSION, fp, &slots);
+    let (nonce_b64, ciphertext_b64) = aes_encrypt(data_key, master_password.as_bytes(), &aad)?;
+    Ok(KeySlotFile { version: MASTER_FILE_VERSION, nonce_b64, ciphertext_b64, slots })
+}
+
+// Decrypts a v1 (ChaCha20-Poly1305) or v2 (AES-GCM) file, the master sealed under the viewer key
+fn open_legacy(viewer_password: &str, data: &[u8]) -> Result<Zeroizing<String>, CryptoError> {
+    let parsed: MasterFile = serde_json::from_slice(data)?;
+    let salt = unb64(&parsed.salt_b64)?;
+    let nonce_bytes = unb64(&parsed.nonce_b64)?;
+    let ciphertext = unb64(&parsed.ciphertext_b64)?;
+    // both ciphers take 96-bit nonces; from_slice panics on any other length
+    if nonce_bytes.len() != 12 { return Err(CryptoError::Decryption); }
+
+    // these files don't record their cost: it was the default of the platform that wrote them,
+    // so a file from a phone opens on a desktop and vice versa
+    let other = if default_mem() == ARGON_MEM_DESKTOP { ARGON_MEM_MOBILE } else { ARGON_MEM_DESKTOP };
+    for mem_kib in [default_mem(), other] {
+        let kdf = KdfParams { mem_kib, ..KdfParams::default() };
+        let key_bytes = Zeroizing::new(derive_key(viewer_password, &salt, &kdf)?);
+        let plaintext = match parsed.version {
+            2 => {
+                let key = Key::<Aes256Gcm>::from_slice(key_bytes.as_ref());
+                Aes256Gcm::new(key).decrypt(Nonce::from_slice(&nonce_bytes), ciphertext.as_ref())
+            },
+            // Legacy v1 used ChaCha20-Poly1305
+            1 => {
+                let key = ChKey::from_slice(key_bytes.as_ref());
+                ChaCha20Poly1305::new(key).decrypt(ChNonce::from_slice(&nonce_bytes), ciphertext.as_ref())
+            }
+            v => return Err(CryptoError::UnsupportedVersion(v)),
+        };
+        if let Ok(plaintext) = plaintext {
+            return String::from_utf8(plaintext).map(Zeroizing::new).map_err(|_| CryptoError::Decryption);
+        }
+    }
+    Err(CryptoError::Decryption)
+}
+
+/// A master file opened with one of its secrets, ready to be sealed again as v4
+struct Unlocked {
+    master: Zeroizing<String>,
+    data_key: Zeroizing<[u8; 32]>,
+    slots: Vec<KeySlot>,
+    /// index of the slot the secret opened
+    slot: usize,
+    /// read from an older version; `slots` are its conversion (a single default slot for v1/v2)
+    converted: bool,
+}
+
+impl Unlocked {
+    fn seal(&self, fp: &str) -> Result<KeySlotFile, CryptoError> {
+        seal(fp, &self.master, &self.data_key, self.slots.clone())
+    }
+}
+
+// Opens a master file and refuses one found under another master's name: before v4 nothing
+// bound a file to its name, and a converted file is sealed again under the name it was read from.
+// Nor did anything bind its key slots and KDF parameters, so once a master has a v4 file an
+// older one in its place is a rolled-back copy and refused too.
+fn unlock(secret: &str, fp: &str, data: &[u8]) -> Result<Unlocked, CryptoError> {
+    let u = open_file(secret, fp, data)?;
+    if u.converted {
+        if config::read_prefs().authenticated_masters.iter().any(|f| f == fp) { return Err(CryptoError::Tampered); }
+        check_file_name(&u, fp, &install_salt()?)?;
+    }
+    Ok(u)
+}
 
-    let file = MasterFile {
-        version: 2,
-        salt_b64: general_purpose::STANDARD_NO_PAD.encode(&salt),
-        nonce_b64: general_purpose::STANDARD_NO_PAD.encode(&nonce_bytes),
-        ciphertext_b64: general_purpose::STANDARD_NO_PAD.encode(&ciphertext),
+// Records that `fp` now has a v4 file; called only once it is in place, as older files stop
+// opening for `fp` from then on
+fn mark_authenticated(fp: &str) -> Result<(), CryptoError> {
+    let mut prefs = config::read_prefs();
+    if !prefs.authenticated_masters.iter().any(|f| f == fp) {
+        prefs.authenticated_masters.push(fp.to_string());
+        config::write_prefs(&prefs)?;
+    }
+    Ok(())
+}
+
+fn check_file_name(u: &Unlocked, fp: &str, install_salt: &[u8]) -> Result<(), CryptoError> {
+    if is_legacy_fingerprint(&u.master, fp) || keyed_fingerprint(&u.master, install_salt) == fp { Ok(()) } else { Err(CryptoError::Tampered) }
+}
+
+// Tries every slot the secret could open; each costs one Argon2id run. Versions this build
+// doesn't know are refused rather than guessed at.
+fn open_file(secret: &str, fp: &str, data: &[u8]) -> Result<Unlocked, CryptoError> {
+    let Versioned { version } = serde_json::from_slice(data)?;
+    let authenticated = match version {
+        1 | 2 => {
+            let master = open_legacy(secret, data)?;
+            let data_key = new_data_key();
+            let slots = vec![wrap_slot(DEFAULT_SLOT, false, secret, &data_key, KdfParams::default())?];
+            return Ok(Unlocked { master, data_key, slots, slot: 0, converted: true });
+        }
+        3 => false,
+        MASTER_FILE_VERSION => true,
+        v => return Err(CryptoError::UnsupportedVersion(v)),
     };
+    let file: KeySlotFile = serde_json::from_slice(data)?;
+    let aad = if authenticated { header_aad(version, fp, &file.slots) } else { Vec::new() };
+    let code = normalize_recovery_code(secret);
+    for (slot, ks) in file.slots.iter().enumerate() {
+        let secret = match (&code, ks.recovery) {
+            (Some(code), true) => code.as_str(),
+            (None, true) => continue,
+            (_, false) => secret,
+        };
+        let Ok(data_key) = unwrap_slot(secret, ks) else { continue };
+        // the slot opened, so a failure here means the header or ciphertext was changed
+        let plaintext = aes_decrypt(&data_key, &file.nonce_b64, &file.ciphertext_b64, &aad).map_err(|_| CryptoError::Tampered)?;
+        let master = Zeroizing::new(String::from_utf8(plaintext.to_vec()).map_err(|_| CryptoError::Decryption)?);
+        return Ok(Unlocked { master, data_key, slots: file.slots, slot, converted: version != MASTER_FILE_VERSION });
+    }
+    Err(CryptoError::Decryption)
+}
+
+// The master of a v4 file, decrypted with its data key directly rather than through a slot
+fn open_with_data_key(data_key: &[u8; 32], fp: &str, data: &[u8]) -> Result<Zeroizing<String>, CryptoError> {
+    let file: KeySlotFile = serde_json::from_slice(data)?;
+    if file.version != MASTER_FILE_VERSION { return Err(CryptoError::UnsupportedVersion(file.version)); }
+    let plaintext = aes_decrypt(data_key, &file.nonce_b64, &file.ciphertext_b64, &header_aad(file.version, fp, &file.slots))?;
+    String::from_utf8(plaintext.to_vec()).map(Zeroizing::new).map_err(|_| CryptoError::Decryption)
+}
+
+fn read_master_file(fp: &str) -> Result<Vec<u8>, CryptoError> {
+    let path = master_file_path_for(fp);
+    if !path.exists() { return Err(CryptoError::NotFound); }
+    Ok(fs::read(path)?)
+}
 
-    // best-effort zeroize sensitive material
-    let mut vp = viewer_password.as_bytes().to_vec();
-    vp.zeroize();
+/// Seals `u` as the master file of `fp`. The new file is written next to the old one and must
+/// give back the master for `secret` before it replaces it.
+fn replace_master_file(fp: &str, u: &Unlocked, secret: &str) -> Result<(), CryptoError> {
+    replace_master_file_with(fp, u, |data| unlock(secret, fp, data).map(|v| v.master))
+}
 
-    let data = serde_json::to_vec_pretty(&file)?;
-    // fingerprint as md5 hex of plaintext master
-    let digest = md5::compute(master_password.as_bytes());
-    let fp = format!("{:x}", digest);
-    let path = master_file_path_for(&fp);
-    fs::write(path, data)?;
+fn replace_master_file_with(fp: &str, u: &Unlocked, reopen: impl Fn(&[u8]) -> Result<Zeroizing<String>, CryptoError>) -> Result<(), CryptoError> {
+    let path = master_file_path_for(fp);
+    let staged = path.with_extension("enc.new");
+    write_atomic(&staged, &serde_json::to_vec_pretty(&u.seal(fp)?)?)?;
+    let verified = reopen(&fs::read(&staged)?);
+    if !matches!(&verified, Ok(s) if **s == *u.master) {
+        let _ = fs::remove_file(&staged);
+        return Err(verified.err().unwrap_or(CryptoError::Decryption));
+    }
+    fs::rename(&staged, &path)?;
+    mark_authenticated(fp)
+}
+
+pub fn save_master(viewer_password: &str, master_password: &str) -> Result<String, CryptoError> {
+    let _ = masters_dir();
+    let data_key = new_data_key();
+    let fp = fingerprint(master_password)?;
+    let file = seal(&fp, master_password, &data_key, vec![wrap_slot(DEFAULT_SLOT, false, viewer_password, &data_key, KdfParams::default())?])?;
+    write_atomic(&master_file_path_for(&fp), &serde_json::to_vec_pretty(&file)?)?;
+    mark_authenticated(&fp)?;
     Ok(fp)
 }
 
+/// Decrypts the master with a viewer password or recovery code; older files are rewritten as
+/// v4 on the way (best-effort).
 pub fn load_master(viewer_password: &str, fingerprint: &str) -> Result<String, CryptoError> {
-    let path = master_file_path_for(fingerprint);
-    if !path.exists() { return Err(CryptoError::NotFound); }
-    let data = fs::read(path)?;
-    let parsed: MasterFile = serde_json::from_slice(&data)?;
-    let salt = general_purpose::STANDARD_NO_PAD.decode(parsed.salt_b64).map_err(|_| CryptoError::Decryption)?;
-    let nonce_bytes = general_purpose::STANDARD_NO_PAD.decode(parsed.nonce_b64).map_err(|_| CryptoError::Decryption)?;
-    let ciphertext = general_purpose::STANDARD_NO_PAD.decode(parsed.ciphertext_b64).map_err(|_| CryptoError::Decryption)?;
-
-    let key_bytes = derive_key(viewer_password, &salt);
-    match parsed.version {
-        2 => {
-            let key = Key::<Aes256Gcm>::from_slice(&key_bytes);
-            let cipher = Aes256Gcm::new(key);
-            let nonce = Nonce::from_slice(&nonce_bytes);
-            let plaintext = cipher
-                .decrypt(nonce, ciphertext.as_ref())
-                .map_err(|_| CryptoError::Decryption)?;
-            let s = String::from_utf8(plaintext).map_err(|_| CryptoError::Decryption)?;
-            Ok(s)
-        },
-        1 | _ => {
-            // Legacy v1 used ChaCha20-Poly1305; decrypt and migrate to v2 (AES-GCM)
-            let key = ChKey::from_slice(&key_bytes);
-            let cipher = ChaCha20Poly1305::new(key);
-            let nonce = ChNonce::from_slice(&nonce_bytes);
-            let plaintext = cipher
-                .decrypt(nonce, ciphertext.as_ref())
-                .map_err(|_| CryptoError::Decryption)?;
-            let s = String::from_utf8(plaintext.clone()).map_err(|_| CryptoError::Decryption)?;
-            // migrate to v2 in-place best-effort
-            if !plaintext.is_empty() {
-                let key_v2 = Key::<Aes256Gcm>::from_slice(&key_bytes);
-                let cipher_v2 = Aes256Gcm::new(key_v2);
-                let mut new_nonce = [0u8; 12];
-                OsRng.fill_bytes(&mut new_nonce);
-                if let Ok(ct2) = cipher_v2.encrypt(Nonce::from_slice(&new_nonce), s.as_bytes()) {
-                    let new_file = MasterFile {
-                        version: 2,
-                        salt_b64: general_purpose::STANDARD_NO_PAD.encode(&salt),
-                        nonce_b64: general_purpose::STANDARD_NO_PAD.encode(&new_nonce),
-                        ciphertext_b64: general_purpose::STANDARD_NO_PAD.encode(&ct2),
-                    };
-                    if let Ok(bytes) = serde_json::to_vec_pretty(&new_file) {
-                        let _ = fs::write(master_file_path_for(fingerprint), bytes);
-                    }
-                }
-            }
-            Ok(s)
+    let u = unlock(viewer_password, fingerprint, &read_master_file(fingerprint)?)?;
+    if u.converted && !u.master.is_empty() {
+        if let Ok(bytes) = u.seal(fingerprint).and_then(|f| Ok(serde_json::to_vec_pretty(&f)?)) {
+            if write_atomic(&master_file_path_for(fingerprint), &bytes).is_ok() { let _ = mark_authenticated(fingerprint); }
         }
     }
+    Ok(u.master.to_string())
+}
+
+/// Replaces the viewer password that opens a master with a new one (fresh salt and nonce);
+/// other slots keep working. Opened with the recovery code it resets the default slot
+/// instead, so a forgotten viewer password can be replaced while the code stays valid.
+pub fn rekey_master(old_viewer: &str, new_viewer: &str, fp: &str) -> Result<(), CryptoError> {
+    let mut u = unlock(old_viewer, fp, &read_master_file(fp)?)?;
+    let target = if u.slots[u.slot].recovery {
+        u.slots.iter().position(|s| !s.recovery && s.label == DEFAULT_SLOT)
+    } else { Some(u.slot) };
+    let (label, params) = target.map_or((DEFAULT_SLOT.to_string(), KdfParams::default()), |i| (u.slots[i].label.clone(), u.slots[i].params));
+    let slot = wrap_slot(&label, false, new_viewer, &u.data_key, params)?;
+    match target { Some(i) => u.slots[i] = slot, None => u.slots.push(slot) }
+    replace_master_file(fp, &u, new_viewer)
+}
+
+/// Key slots of a master by label; a v1/v2 file counts as its single default slot
+pub fn list_key_slots(fp: &str) -> Result<Vec<SlotInfo>, CryptoError> {
+    let data = read_master_file(fp)?;
+    let Versioned { version } = serde_json::from_slice(&data)?;
+    match version {
+        1 | 2 => return Ok(vec![SlotInfo { label: DEFAULT_SLOT.into(), recovery: false, params: KdfParams::default() }]),
+        3 | MASTER_FILE_VERSION => {}
+        v => return Err(CryptoError::UnsupportedVersion(v)),
+    }
+    // unauthenticated until the file is unlocked
+    let file: KeySlotFile = serde_json::from_slice(&data)?;
+    Ok(file.slots.into_iter().map(|s| SlotInfo { label: s.label, recovery: s.recovery, params: s.params }).collect())
+}
+
+/// Lets `new_viewer` open the master too, under a label of its own and with the Argon2id cost of
+/// the slot `viewer_password` opened
+pub fn add_key_slot(viewer_password: &str, fp: &str, label: &str, new_viewer: &str) -> Result<(), CryptoError> {
+    let label = label.trim();
+    if label.is_empty() { return Err(CryptoError::Slot("slot label is empty".into())); }
+    let mut u = unlock(viewer_password, fp, &read_master_file(fp)?)?;
+    if u.slots.iter().any(|s| s.label == label) { return Err(CryptoError::Slot(format!("a key slot labelled {:?} already exists", label))); }
+    let params = u.slots[u.slot].params;
+    u.slots.push(wrap_slot(label, false, new_viewer, &u.data_key, params)?);
+    replace_master_file(fp, &u, new_viewer)
+}
+
+/// Generates a recovery code that opens the master, replacing any earlier one. It is returned
+/// once, formatted for printing, and never stored.
+pub fn add_recovery_slot(viewer_password: &str, fp: &str) -> Result<Zeroizing<String>, CryptoError> {
+    let mut u = unlock(viewer_password, fp, &read_master_file(fp)?)?;
+    let code = new_recovery_code();
+    let canonical = normalize_recovery_code(&code).ok_or(CryptoError::Decryption)?;
+    u.slots.retain(|s| !s.recovery);
+    u.slots.push(wrap_slot(RECOVERY_SLOT, true, &canonical, &u.data_key, KdfParams::default())?);
+    replace_master_file(fp, &u, &canonical)?;
+    Ok(code)
+}
+
+/// Re-wraps the slot `viewer_password` opens under `params`, which must be at least as strong as
+/// its current ones; other slots keep theirs until upgraded with their own password.
+pub fn upgrade_kdf(viewer_password: &str, fp: &str, params: KdfParams) -> Result<(), CryptoError> {
+    params.check()?;
+    let mut u = unlock(viewer_password, fp, &read_master_file(fp)?)?;
+    let slot = &u.slots[u.slot];
+    // 160 random bits gain nothing from a slower KDF
+    if slot.recovery { return Err(CryptoError::Slot("the recovery code slot has no parameters to upgrade".into())); }
+    if !params.at_least(&slot.params) {
+        return Err(CryptoError::Kdf(format!("{:?} is weaker than the slot's current {:?}", params, slot.params)));
+    }
+    u.slots[u.slot] = wrap_slot(&slot.label.clone(), false, viewer_password, &u.data_key, params)?;
+    replace_master_file(fp, &u, viewer_password)
+}
+
+/// Removes the slot labelled `label`; at least one viewer-password slot always remains
+pub fn remove_key_slot(viewer_password: &str, fp: &str, label: &str) -> Result<(), CryptoError> {
+    let mut u = unlock(viewer_password, fp, &read_master_file(fp)?)?;
+    let Some(i) = u.slots.iter().position(|s| s.label == label) else { return Err(CryptoError::SlotNotFound); };
+    let removed = u.slots.remove(i);
+    if !removed.recovery && u.slots.iter().all(|s| s.recovery) {
+        return Err(CryptoError::Slot("cannot remove the last viewer password".into()));
+    }
+    if i == u.slot {
+        // the secret given no longer opens the file, so the staged one is checked with the data key
+        return replace_master_file_with(fp, &u, |data| open_with_data_key(&u.data_key, fp, data));
+    }
+    replace_master_file(fp, &u, viewer_password)
 }
 
-pub fn has_master() -> bool { masters_dir().exists() && fs::read_dir(masters_dir()).map(|mut it| it.next().is_some()).unwrap_or(false) }
+pub fn has_master() -> bool { !list_master_fingerprints().is_empty() }
 
 pub fn list_master_fingerprints() -> Vec<String> {
     let dir = masters_dir();
     let mut v = vec![];
     if let Ok(rd) = fs::read_dir(dir) {
         for e in rd.flatten() {
-            if let Some(name) = e.path().file_stem().and_then(|s| s.to_str()) { v.push(name.to_string()); }
+            let path = e.path();
+            // only `<fingerprint>.enc`; skips the temporaries of interrupted writes
+            if path.extension().and_then(|x| x.to_str()) != Some("enc") { continue; }
+            if let Some(name) = path.file_stem().and_then(|s| s.to_str()) { v.push(name.to_string()); }
         }
     }
     v
 }
 
-pub fn master_fingerprint(viewer_password: &str, fingerprint: &str) -> Result<String, CryptoError> {
-    let master = load_master(viewer_password, fingerprint)?;
-    let digest = md5::compute(master.as_bytes());
-    Ok(format!("{:x}", digest))
-}
-
 pub fn delete_master(fp: &str) -> bool {
     let path = master_file_path_for(fp);
-    if path.exists() { std::fs::remove_file(path).is_ok() } else { false }
+    if !path.exists() || std::fs::remove_file(path).is_err() { return false; }
+    let mut prefs = config::read_prefs();
+    prefs.authenticated_masters.retain(|f| f != fp);
+    let _ = config::write_prefs(&prefs);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keyed_fingerprints_depend_on_the_install_salt() {
+        let salt = [7u8; INSTALL_SALT_LEN];
+        let fp = keyed_fingerprint("test", &salt);
+        assert_eq!(fp.len(), 32);
+        assert!(fp.bytes().all(|b| b.is_ascii_hexdigit()));
+        assert_eq!(fp, keyed_fingerprint("test", &salt));
+        assert_ne!(fp, keyed_fingerprint("test", &[8u8; INSTALL_SALT_LEN]));
+        assert_ne!(fp, keyed_fingerprint("test2", &salt));
+        assert!(!is_legacy_fingerprint("test", &fp));
+        assert!(is_legacy_fingerprint("test", "098f6bcd4621d373cade4e832627b4f6"));
+    }
+
+    #[test]
+    fn every_key_slot_opens_the_same_master() {
+        let data_key = new_data_key();
+        let code = new_recovery_code();
+        let canonical = normalize_recovery_code(&code).unwrap();
+        let slots = vec![
+            wrap_slot(DEFAULT_SLOT, false, "alice", &data_key, KdfParams::default()).unwrap(),
+            wrap_slot("bob", false, "bob's viewer", &data_key, KdfParams::default()).unwrap(),
+            wrap_slot(RECOVERY_SLOT, true, &canonical, &data_key, KdfParams::default()).unwrap(),
+        ];
+        let data = serde_json::to_vec(&seal("fp", "master", &data_key, slots).unwrap()).unwrap();
+        for (secret, slot) in [("alice", 0), ("bob's viewer", 1), (code.as_str(), 2)] {
+            let u = unlock(secret, "fp", &data).unwrap();
+            assert_eq!((u.master.as_str(), u.slot, u.converted), ("master", slot, false));
+        }
+        // typed in lowercase, without dashes and with O/I for 0/1
+        let typed = canonical.to_lowercase().replace('0', "o").replace('1', "i");
+        assert_eq!(unlock(&typed, "fp", &data).unwrap().slot, 2);
+        assert!(matches!(unlock("carol", "fp", &data), Err(CryptoError::Decryption)));
+    }
+
+    #[test]
+    fn recovery_codes_are_printable_and_normalize() {
+        let code = new_recovery_code();
+        assert_eq!(code.len(), 39);
+        assert!(code.split('-').all(|g| g.len() == 4));
+        assert_ne!(*code, *new_recovery_code());
+        assert!(normalize_recovery_code("correct horse battery staple").is_none());
+        assert!(normalize_recovery_code("").is_none());
+    }
+
+    #[test]
+    fn legacy_v2_files_unlock_as_a_single_default_slot() {
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        let key = derive_key("viewer", &salt, &KdfParams::default()).unwrap();
+        let (nonce_b64, ciphertext_b64) = aes_encrypt(&key, b"master", &[]).unwrap();
+        let v2 = MasterFile { version: 2, salt_b64: b64(&salt), nonce_b64, ciphertext_b64 };
+        let u = open_file("viewer", "fp", &serde_json::to_vec(&v2).unwrap()).unwrap();
+        assert!(u.converted);
+        assert_eq!(u.slots.len(), 1);
+        assert_eq!(u.slots[0].label, DEFAULT_SLOT);
+        let converted = serde_json::to_vec(&u.seal("fp").unwrap()).unwrap();
+        let u = unlock("viewer", "fp", &converted).unwrap();
+        assert_eq!((u.master.as_str(), u.converted), ("master", false));
+        assert!(unlock("other", "fp", &converted).is_err());
+    }
+
+    #[test]
+    fn key_slots_record_their_kdf_params() {
+        let data_key = new_data_key();
+        let light = KdfParams { mem_kib: ARGON_MEM_MOBILE, iterations: 1, parallelism: 1 };
+        let slot = wrap_slot("phone", false, "viewer", &data_key, light).unwrap();
+        let mut json = serde_json::to_value(&slot).unwrap();
+        assert_eq!((json["kdf"].as_str(), json["mem_kib"].as_u64(), json["iterations"].as_u64()), (Some("argon2id"), Some(8192), Some(1)));
+        assert_eq!(*unwrap_slot("viewer", &slot).unwrap(), *data_key);
+
+        // other parameters derive another key
+        json["iterations"] = 2.into();
+        assert!(unwrap_slot("viewer", &serde_json::from_value(json.clone()).unwrap()).is_err());
+        // and a crafted file can't ask for unbounded work
+        json["mem_kib"] = (ARGON_MEM_MAX + 1).into();
+        assert!(matches!(unwrap_slot("viewer", &serde_json::from_value(json.clone()).unwrap()), Err(CryptoError::Kdf(_))));
+        json["kdf"] = "scrypt".into();
+        assert!(matches!(unwrap_slot("viewer", &serde_json::from_value(json.clone()).unwrap()), Err(CryptoError::Kdf(_))));
+
+        // slots written before the parameters were recorded
+        for key in ["kdf", "mem_kib", "iterations", "parallelism"] { json.as_object_mut().unwrap().remove(key); }
+        let old: KeySlot = serde_json::from_value(json).unwrap();
+        assert_eq!((old.kdf.as_str(), old.params), ("argon2id", KdfParams::default()));
+    }
+
+    #[test]
+    fn calibration_never_goes_below_the_default() {
+        assert_eq!(calibrate_kdf(Duration::ZERO).unwrap(), KdfParams::default());
+        let strong = KdfParams { mem_kib: 65536, iterations: 3, parallelism: 1 };
+        assert!(strong.at_least(&KdfParams::default()));
+        assert!(!KdfParams::default().at_least(&strong));
+        // more passes don't make up for less memory
+        assert!(!KdfParams { mem_kib: ARGON_MEM_MOBILE, iterations: 20, parallelism: 1 }.at_least(&KdfParams { mem_kib: ARGON_MEM_DESKTOP, iterations: 2, parallelism: 1 }));
+    }
+
+    #[test]
+    fn tampered_headers_are_rejected() {
+        let light = KdfParams { mem_kib: ARGON_MEM_MOBILE, iterations: 1, parallelism: 1 };
+        let data_key = new_data_key();
+        let slots = vec![
+            wrap_slot(DEFAULT_SLOT, false, "alice", &data_key, light).unwrap(),
+            wrap_slot("bob", false, "bob", &data_key, light).unwrap(),
+        ];
+        let file = serde_json::to_value(seal("fp", "master", &data_key, slots.clone()).unwrap()).unwrap();
+        let open = |v: &serde_json::Value, fp: &str| unlock("alice", fp, &serde_json::to_vec(v).unwrap()).map(|u| u.master.to_string());
+        assert_eq!(open(&file, "fp").unwrap(), "master");
+        // moved to another master's name
+        assert!(matches!(open(&file, "other"), Err(CryptoError::Tampered)));
+
+        let tampered = |edit: &dyn Fn(&mut serde_json::Value)| { let mut v = file.clone(); edit(&mut v); open(&v, "fp") };
+        assert!(matches!(tampered(&|v| v["slots"][1]["label"] = "mallory".into()), Err(CryptoError::Tampered)));
+        assert!(matches!(tampered(&|v| v["slots"][1]["recovery"] = true.into()), Err(CryptoError::Tampered)));
+        assert!(matches!(tampered(&|v| v["slots"][1]["iterations"] = 3.into()), Err(CryptoError::Tampered)));
+        assert!(matches!(tampered(&|v| { v["slots"].as_array_mut().unwrap().pop(); }), Err(CryptoError::Tampered)));
+        // downgrades: v3 carries no associated data, v1 is another layout, unknown versions are refused
+        assert!(matches!(tampered(&|v| v["version"] = 3.into()), Err(CryptoError::Tampered)));
+        assert!(matches!(tampered(&|v| v["version"] = 1.into()), Err(CryptoError::Json(_))));
+        assert!(matches!(tampered(&|v| v["version"] = 5.into()), Err(CryptoError::UnsupportedVersion(5))));
+
+        // v3 files from before the header was authenticated still open, and are due for a rewrite
+        let (nonce_b64, ciphertext_b64) = aes_encrypt(&data_key, b"master", &[]).unwrap();
+        let v3 = KeySlotFile { version: 3, nonce_b64, ciphertext_b64, slots };
+        let u = open_file("bob", "fp", &serde_json::to_vec(&v3).unwrap()).unwrap();
+        assert_eq!((u.master.as_str(), u.slot, u.converted), ("master", 1, true));
+    }
+
+    #[test]
+    fn sealed_files_open_with_their_data_key() {
+        let light = KdfParams { mem_kib: ARGON_MEM_MOBILE, iterations: 1, parallelism: 1 };
+        let data_key = new_data_key();
+        let slots = vec![wrap_slot("bob", false, "bob", &data_key, light).unwrap()];
+        let data = serde_json::to_vec(&seal("fp", "master", &data_key, slots).unwrap()).unwrap();
+        assert_eq!(open_with_data_key(&data_key, "fp", &data).unwrap().as_str(), "master");
+        assert!(open_with_data_key(&new_data_key(), "fp", &data).is_err());
+        assert!(open_with_data_key(&data_key, "other", &data).is_err());
+    }
+
+    #[test]
+    fn tampered_legacy_nonces_are_rejected() {
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        let key = derive_key("viewer", &salt, &KdfParams::default()).unwrap();
+        let (_, ciphertext_b64) = aes_encrypt(&key, b"master", &[]).unwrap();
+        // both ciphers panic on a nonce that isn't 12 bytes; the file is refused instead
+        for (version, nonce) in [(2, vec![0u8; 8]), (2, vec![0u8; 24]), (1, Vec::new()), (1, vec![0u8; 13])] {
+            let file = MasterFile { version, salt_b64: b64(&salt), nonce_b64: b64(&nonce), ciphertext_b64: ciphertext_b64.clone() };
+            assert!(matches!(open_file("viewer", "fp", &serde_json::to_vec(&file).unwrap()), Err(CryptoError::Decryption)));
+        }
+    }
+
+    #[test]
+    fn v3_files_under_another_name_are_refused() {
+        let light = KdfParams { mem_kib: ARGON_MEM_MOBILE, iterations: 1, parallelism: 1 };
+        let salt = [7u8; INSTALL_SALT_LEN];
+        let data_key = new_data_key();
+        let (nonce_b64, ciphertext_b64) = aes_encrypt(&data_key, b"master", &[]).unwrap();
+        let slots = vec![wrap_slot(DEFAULT_SLOT, false, "alice", &data_key, light).unwrap()];
+        let v3 = serde_json::to_vec(&KeySlotFile { version: 3, nonce_b64, ciphertext_b64, slots }).unwrap();
+        let fp = keyed_fingerprint("master", &salt);
+        assert!(check_file_name(&open_file("alice", &fp, &v3).unwrap(), &fp, &salt).is_ok());
+        // still named after the MD5 of its master, as before the keyed fingerprints
+        assert!(check_file_name(&open_file("alice", "eb0a191797624dd3a48fa681d3061212", &v3).unwrap(), "eb0a191797624dd3a48fa681d3061212", &salt).is_ok());
+        // copied or renamed to another master's fingerprint: v3 authenticates no name, so the
+        // file itself opens, but it must not be sealed again as v4 under that name
+        let other = keyed_fingerprint("other", &salt);
+        let u = open_file("alice", &other, &v3).unwrap();
+        assert!(matches!(check_file_name(&u, &other, &salt), Err(CryptoError::Tampered)));
+    }
+
+    #[test]
+    fn older_files_are_refused_once_a_master_has_a_v4_file() {
+        let _lock = crate::paths::TEST_DATA_DIR.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = tempfile::tempdir().unwrap();
+        std::env::set_var("SAFORIA_DATA_DIR", dir.path());
+        let light = KdfParams { mem_kib: ARGON_MEM_MOBILE, iterations: 1, parallelism: 1 };
+        let v3_file = |master: &str, viewers: &[&str]| {
+            let data_key = new_data_key();
+            let (nonce_b64, ciphertext_b64) = aes_encrypt(&data_key, master.as_bytes(), &[]).unwrap();
+            let slots = viewers.iter().map(|v| wrap_slot(v, false, v, &data_key, light).unwrap()).collect();
+            serde_json::to_vec(&KeySlotFile { version: 3, nonce_b64, ciphertext_b64, slots }).unwrap()
+        };
+
+        // a v3 file never rewritten still opens, and is converted to v4 on the way
+        let fp = fingerprint("master").unwrap();
+        let old = v3_file("master", &["alice", "bob"]);
+        fs::write(master_file_path_for(&fp), &old).unwrap();
+        assert_eq!(load_master("alice", &fp).unwrap(), "master");
+        remove_key_slot("alice", &fp, "bob").unwrap();
+        assert!(load_master("bob", &fp).is_err());
+        // putting the old file back would bring the removed slot back
+        fs::write(master_file_path_for(&fp), &old).unwrap();
+        assert!(matches!(load_master("bob", &fp), Err(CryptoError::Tampered)));
+
+        // a master set up on this build is never opened from an older file
+        let fp = save_master("carol", "second").unwrap();
+        fs::write(master_file_path_for(&fp), v3_file("second", &["carol"])).unwrap();
+        assert!(matches!(load_master("carol", &fp), Err(CryptoError::Tampered)));
+        std::env::remove_var("SAFORIA_DATA_DIR");
+    }
+
+    #[test]
+    fn legacy_files_go_only_once_the_keyed_copy_opens() {
+        let _lock = crate::paths::TEST_DATA_DIR.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = tempfile::tempdir().unwrap();
+        std::env::set_var("SAFORIA_DATA_DIR", dir.path());
+        let legacy_file = |master: &str| {
+            let mut salt = [0u8; 16];
+            OsRng.fill_bytes(&mut salt);
+            let key = derive_key("viewer", &salt, &KdfParams::default()).unwrap();
+            let (nonce_b64, ciphertext_b64) = aes_encrypt(&key, master.as_bytes(), &[]).unwrap();
+            let path = master_file_path_for(&legacy_fingerprint(master));
+            fs::write(path, serde_json::to_vec(&MasterFile { version: 2, salt_b64: b64(&salt), nonce_b64, ciphertext_b64 }).unwrap()).unwrap();
+            legacy_fingerprint(master)
+        };
+
+        let legacy = legacy_file("master");
+        let fp = migrate_legacy_fingerprint(&legacy, "viewer", "master").unwrap();
+        assert!(!master_file_path_for(&legacy).exists());
+        assert_eq!(load_master("viewer", &fp).unwrap(), "master");
+
+        // the keyed name already holds another master: the legacy file is the only copy left
+        let legacy = legacy_file("second");
+        let fp = fingerprint("second").unwrap();
+        fs::copy(master_file_path_for(&fingerprint("master").unwrap()), master_file_path_for(&fp)).unwrap();
+        assert!(migrate_legacy_fingerprint(&legacy, "viewer", "second").is_err());
+        assert!(master_file_path_for(&legacy).exists());
+        std::env::remove_var("SAFORIA_DATA_DIR");
+    }
 }
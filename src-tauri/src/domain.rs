@@ -0,0 +1,124 @@
+use std::{collections::HashSet, sync::OnceLock};
+use url::{Host, Url};
+
+// Snapshot of https://publicsuffix.org/list/public_suffix_list.dat (2023-02-09), ICANN and
+// private sections; Mozilla Public License 2.0, see the header of the file
+const PUBLIC_SUFFIX_LIST: &str = include_str!("public_suffix_list.dat");
+
+struct Rules {
+    exact: HashSet<&'static str>,
+    /// `*.ck` is stored as `ck`
+    wildcard: HashSet<&'static str>,
+    /// `!www.ck` is stored as `www.ck`
+    exception: HashSet<&'static str>,
+}
+
+fn rules() -> &'static Rules {
+    static RULES: OnceLock<Rules> = OnceLock::new();
+    RULES.get_or_init(|| {
+        let mut rules = Rules { exact: HashSet::new(), wildcard: HashSet::new(), exception: HashSet::new() };
+        for line in PUBLIC_SUFFIX_LIST.lines() {
+            // a rule is the first whitespace-separated token of a non-comment line
+            let Some(rule) = line.split_whitespace().next().filter(|r| !r.starts_with("//")) else { continue };
+            if let Some(r) = rule.strip_prefix("*.") { rules.wildcard.insert(r); }
+            else if let Some(r) = rule.strip_prefix('!') { rules.exception.insert(r); }
+            else { rules.exact.insert(rule); }
+        }
+        rules
+    })
+}
+
+/// Host of a URL or bare hostname, lowercase and with IDNs in Unicode form:
+/// `https://user@Accounts.Google.com:443/signin?x` → `accounts.google.com`.
+pub fn host(input: &str) -> Option<String> {
+    let input = input.trim();
+    if input.is_empty() { return None; }
+    let url = if input.contains("://") { Url::parse(input) } else { Url::parse(&format!("http://{}", input)) }.ok()?;
+    match url.host()? {
+        Host::Domain(d) => {
+            let d = url::quirks::domain_to_unicode(d).to_lowercase();
+            let d = d.trim_end_matches('.');
+            (!d.is_empty()).then(|| d.to_string())
+        }
+        Host::Ipv4(ip) => Some(ip.to_string()),
+        Host::Ipv6(ip) => Some(ip.to_string()),
+    }
+}
+
+/// Registrable domain (public suffix plus one label) of a hostname per the Public Suffix List
+/// algorithm: exception rules win, then the longest matching rule, else the last label.
+/// `None` when the host is itself a public suffix.
+pub fn registrable_domain(host: &str) -> Option<String> {
+    let rules = rules();
+    let labels: Vec<&str> = host.split('.').collect();
+    if labels.iter().any(|l| l.is_empty()) { return None; }
+    let suffix = |i: usize| labels[i..].join(".");
+    let public = (0..labels.len())
+        .find(|&i| rules.exception.contains(suffix(i).as_str()))
+        .map(|i| i + 1)
+        .or_else(|| (0..labels.len()).find(|&i| {
+            rules.exact.contains(suffix(i).as_str()) || (i + 1 < labels.len() && rules.wildcard.contains(suffix(i + 1).as_str()))
+        }))
+        .unwrap_or(labels.len() - 1);
+    (public > 0).then(|| suffix(public - 1))
+}
+
+/// Postfix suggested for a pasted URL or hostname: its registrable domain, or the address for
+/// IP hosts. `None` when nothing domain-like can be extracted.
+pub fn canonical_postfix(input: &str) -> Option<String> {
+    let host = host(input)?;
+    if host.parse::<std::net::IpAddr>().is_ok() { return Some(host); }
+    registrable_domain(&host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registrable_domains_follow_the_psl_test_vectors() {
+        // From the list's own test_psl.txt
+        for (host, expected) in [
+            ("com", None),
+            ("example.com", Some("example.com")),
+            ("a.b.example.com", Some("example.com")),
+            ("uk.com", None),
+            ("b.example.uk.com", Some("example.uk.com")),
+            ("c.kobe.jp", None),
+            ("b.c.kobe.jp", Some("b.c.kobe.jp")),
+            ("city.kobe.jp", Some("city.kobe.jp")),
+            ("www.city.kobe.jp", Some("city.kobe.jp")),
+            ("test.ck", None),
+            ("b.test.ck", Some("b.test.ck")),
+            ("www.www.ck", Some("www.ck")),
+            ("test.ak.us", Some("test.ak.us")),
+            ("k12.ak.us", None),
+            ("www.test.k12.ak.us", Some("test.k12.ak.us")),
+            ("公司.cn", None),
+            ("www.食狮.公司.cn", Some("食狮.公司.cn")),
+            ("shishi.中国", Some("shishi.中国")),
+            // unlisted TLDs fall back to the last label
+            ("a.b.example.example", Some("example.example")),
+            ("example", None),
+        ] {
+            assert_eq!(registrable_domain(host).as_deref(), expected, "{}", host);
+        }
+    }
+
+    #[test]
+    fn urls_reduce_to_a_canonical_postfix() {
+        assert_eq!(canonical_postfix("https://accounts.google.com/signin?continue=x#y").as_deref(), Some("google.com"));
+        assert_eq!(canonical_postfix("google.com").as_deref(), Some("google.com"));
+        assert_eq!(canonical_postfix("  HTTPS://User:pw@Login.Example.CO.UK:8443/ ").as_deref(), Some("example.co.uk"));
+        assert_eq!(canonical_postfix("www.example.com.").as_deref(), Some("example.com"));
+        // private-section suffixes keep tenants apart
+        assert_eq!(canonical_postfix("https://alice.github.io/blog").as_deref(), Some("alice.github.io"));
+        // punycode hosts come back in Unicode, matching what people type
+        assert_eq!(canonical_postfix("https://www.xn--85x722f.xn--55qx5d.cn/").as_deref(), Some("食狮.公司.cn"));
+        assert_eq!(canonical_postfix("http://192.168.1.10:8080/admin").as_deref(), Some("192.168.1.10"));
+        assert_eq!(canonical_postfix("https://[::1]:8443/").as_deref(), Some("::1"));
+        for none in ["", "  ", "co.uk", "my bank", "github.io"] {
+            assert_eq!(canonical_postfix(none), None, "{:?}", none);
+        }
+    }
+}
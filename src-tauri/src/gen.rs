@@ -1,5 +1,97 @@
-use sha2::{Sha256, Digest};
+use sha2::{Sha256, Sha512, Digest};
+use hmac::{Hmac, Mac};
+use argon2::{Argon2, Params, Algorithm, Version};
+use pbkdf2::pbkdf2_hmac;
+use scrypt::{scrypt, Params as ScryptParams};
 use base64::{engine::general_purpose::STANDARD, Engine as _};
+use std::{borrow::Cow, fmt, str::FromStr};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+
+type HmacSha256 = Hmac<Sha256>;
+
+const ALNUM_ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+const STRONG_ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789!@#$%^&*()-_=+[]{};:,.?/<>~";
+const LOWER: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+const UPPER: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &[u8] = b"0123456789";
+const SYMBOLS: &[u8] = b"!@#$%^&*()-_=+[]{};:,.?/<>~";
+const LESSPASS_SYMBOLS: &[u8] = b"!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+
+// Domain tag for the v3 family; bump only together with a new method family.
+const V3_TAG: &[u8] = b"saforia/v3";
+const ARGON_TAG: &[u8] = b"saforia/argon";
+const POLICY_TAG: &[u8] = b"saforia/policy";
+const PIN_TAG: &[u8] = b"saforia/pin";
+const BIP39_TAG: &[u8] = b"saforia/bip39";
+const WORDS_TAG: &[u8] = b"saforia/words";
+const SSH_ED25519_TAG: &[u8] = b"saforia/ssh-ed25519";
+const OTP_KEY_TAG: &[u8] = b"saforia/otp-key";
+const USERNAME_TAG: &[u8] = b"saforia/username";
+const ALIAS_TAG: &[u8] = b"saforia/alias";
+const ANSWER_TAG: &[u8] = b"saforia/answer";
+const POLICY_MAX_ATTEMPTS: u32 = 1000;
+
+// Argon2id cost for "argon_" ids that do not spell it out. Unlike crypto::derive_key this
+// must never vary per platform: the same id has to give the same password everywhere.
+const ARGON_GEN_MEM_KIB: u32 = 19456;
+const ARGON_GEN_ITERATIONS: u32 = 2;
+const ARGON_GEN_PARALLELISM: u32 = 1;
+// Upper bounds so an imported entry cannot ask for an unbounded allocation
+const MAX_LEN: usize = 256;
+// These cap the Argon2id cost an imported `argon_m<kib>_t<iter>_p<par>` method id can request
+const ARGON_GEN_MAX_MEM_KIB: u32 = 1024 * 1024;
+const ARGON_GEN_MAX_ITERATIONS: u32 = 16;
+const ARGON_GEN_MAX_PARALLELISM: u32 = 8;
+const PIN_MIN_LEN: usize = 4;
+// Candidates drawn per PIN; for 4 digits about 2% are trivial, so 16 in a row never are in practice
+const PIN_CANDIDATES: usize = 16;
+// LessPass v2 constants; its UI only offers lengths 5..=35, past that the 256-bit entropy runs dry
+const LESSPASS_ITERATIONS: u32 = 100_000;
+const LESSPASS_MIN_LEN: usize = 5;
+const LESSPASS_MAX_LEN: usize = 35;
+// Spectre (Master Password) v3: scope string and scrypt N=32768, r=8, p=2 for the 64-byte master key
+const SPECTRE_SCOPE: &[u8] = b"com.lyndir.masterpassword";
+const SPECTRE_LOG_N: u8 = 15;
+const SPECTRE_R: u32 = 8;
+const SPECTRE_P: u32 = 2;
+// SuperGenPass: at least 10 hash rounds, then more until the prefix passes its checks
+const SGP_MIN_ROUNDS: usize = 10;
+const SGP_MAX_ROUNDS: usize = 10_000;
+const SGP_MIN_LEN: usize = 4;
+const SGP_MAX_LEN: usize = 24;
+// Official BIP39 English list, one word per line (sha256 2f5eed53…3b24dbda)
+const BIP39_WORDLIST: &str = include_str!("bip39_english.txt");
+const BIP39_WORD_COUNTS: [usize; 5] = [12, 15, 18, 21, 24];
+// EFF large wordlist (CC BY 3.0 US), dice number and word per line, 7776 words (sha256 addd3553…996b903e)
+const EFF_WORDLIST: &str = include_str!("eff_large_wordlist.txt");
+const EFF_WORD_COUNT: usize = 7776;
+const WORDS_MIN: usize = 4;
+const WORDS_MAX: usize = 20;
+// Known-answer corpus for every method family; outputs must never change, so vectors are only
+// ever added. Shared with scripts/legacy-parity.mjs and run by the `self_test` command.
+const KNOWN_ANSWERS: &str = include_str!("known_answers.json");
+// Pronounceable handles: consonant-vowel syllables; letters that read ambiguously (c, q, w, x, y) are left out
+const CONSONANTS: &[u8] = b"bdfghjklmnprstvz";
+const VOWELS: &[u8] = b"aeiou";
+const USERNAME_SYLLABLES: usize = 4;
+const USERNAME_DIGITS: usize = 2;
+const ALIAS_SYLLABLES: usize = 5;
+const ANSWER_SYLLABLES: usize = 6;
+const ANSWER_WORDS: usize = 4;
+/// Placeholder replaced by the derived local-part in an email alias template
+pub const ALIAS_PLACEHOLDER: &str = "{alias}";
+
+const BASE64_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const URL_BASE64_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_.";
+// Effective entropy never exceeds the key material behind a method: 128 bits for MD5-based
+// methods, 256 for everything built on SHA-256 (a 256-char password is not 1600 bits strong)
+const MD5_BITS: f64 = 128.0;
+const KEY_BITS: f64 = 256.0;
+// Two-label suffixes after which SuperGenPass/PwdHash keep a third label ("example.co.uk"), one per
+// line in the order of Chris Zarate's bookmarklet code, which PwdHash's extractDomain ships verbatim
+const BOOKMARKLET_SUFFIXES: &str = include_str!("bookmarklet_domains.txt");
 
 fn sha256(data: &[u8]) -> [u8; 32] {
     let mut hasher = Sha256::new();
@@ -13,7 +105,7 @@ fn extend_stream(seed: Vec<u8>, need: usize) -> Vec<u8> {
     while out.len() < need {
         let mut h = Sha256::new();
         h.update(&out[out.len().saturating_sub(32)..]);
-        h.update(&counter.to_le_bytes());
+        h.update(counter.to_le_bytes());
         out.extend_from_slice(&h.finalize());
         counter = counter.wrapping_add(1);
     }
@@ -34,19 +126,451 @@ fn map_to_alphabet(mut stream: Vec<u8>, alphabet: &[u8], size: usize) -> String
     out
 }
 
+// Length-prefixed concatenation: every field is preceded by its u32 big-endian length,
+// so no choice of field contents can shift bytes from one field into another.
+pub fn frame(fields: &[&[u8]]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for f in fields {
+        out.extend_from_slice(&(f.len() as u32).to_be_bytes());
+        out.extend_from_slice(f);
+    }
+    out
+}
+
+// HMAC-SHA256 in counter mode keyed with the master: block i = HMAC(master, msg || i_be32).
+fn hmac_stream(key: &[u8], msg: &[u8], need: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(need + 32);
+    let mut block: u32 = 0;
+    while out.len() < need {
+        let mut mac = HmacSha256::new_from_slice(key).expect("hmac accepts keys of any length");
+        mac.update(msg);
+        mac.update(&block.to_be_bytes());
+        out.extend_from_slice(&mac.finalize().into_bytes());
+        block = block.wrapping_add(1);
+    }
+    out.truncate(need);
+    out
+}
+
+/// NFC form of `s`, borrowed when it already is (as all ASCII is). Composed and decomposed
+/// spellings of the same text — macOS and Android keyboards differ — then give the same bytes.
+pub fn nfc(s: &str) -> Cow<'_, str> {
+    if is_nfc(s) { Cow::Borrowed(s) } else { Cow::Owned(s.nfc().collect()) }
+}
+
 fn b64_no_pad(data: &[u8]) -> String { STANDARD.encode(data).trim_end_matches('=').to_string() }
 
-pub fn generate(master: &str, postfix: &str, method_id: &str) -> String {
-    match method_id {
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum GenError {
+    #[error("unknown method: {0}")] UnknownMethod(String),
+    #[error("invalid method parameters: {0}")] InvalidParams(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Charset { Alnum, Strong }
+
+impl Charset {
+    pub fn as_str(self) -> &'static str {
+        match self { Charset::Alnum => "alnum", Charset::Strong => "strong" }
+    }
+
+    fn alphabet(self) -> &'static [u8] {
+        match self { Charset::Alnum => ALNUM_ALPHABET, Charset::Strong => STRONG_ALPHABET }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s { "alnum" => Some(Charset::Alnum), "strong" => Some(Charset::Strong), _ => None }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SgpHash { Md5, Sha512 }
+
+impl SgpHash {
+    pub fn as_str(self) -> &'static str {
+        match self { SgpHash::Md5 => "md5", SgpHash::Sha512 => "sha512" }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s { "md5" => Some(SgpHash::Md5), "sha512" => Some(SgpHash::Sha512), _ => None }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WordSep { Dash, Dot, Space, Underscore }
+
+impl WordSep {
+    pub fn as_str(self) -> &'static str {
+        match self { WordSep::Dash => "dash", WordSep::Dot => "dot", WordSep::Space => "space", WordSep::Underscore => "underscore" }
+    }
+
+    fn separator(self) -> &'static str {
+        match self { WordSep::Dash => "-", WordSep::Dot => ".", WordSep::Space => " ", WordSep::Underscore => "_" }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "dash" => Some(WordSep::Dash),
+            "dot" => Some(WordSep::Dot),
+            "space" => Some(WordSep::Space),
+            "underscore" => Some(WordSep::Underscore),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArgonCost { pub mem_kib: u32, pub iterations: u32, pub parallelism: u32 }
+
+impl Default for ArgonCost {
+    fn default() -> Self {
+        ArgonCost { mem_kib: ARGON_GEN_MEM_KIB, iterations: ARGON_GEN_ITERATIONS, parallelism: ARGON_GEN_PARALLELISM }
+    }
+}
+
+/// Parsed form of a `method_id`. Only the canonical spelling parses, so
+/// `id.parse::<MethodSpec>()?.to_string() == id` always holds and two different
+/// ids can never select the same derivation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MethodSpec {
+    LegacyV1,
+    LegacyV2,
+    /// `len<N>_<charset>`
+    Len { len: usize, charset: Charset },
+    /// `v3_len<N>_<charset>`
+    V3 { len: usize, charset: Charset },
+    /// `argon_len<N>_<charset>`, or `argon_m<kib>_t<iter>_p<par>_len<N>_<charset>` for non-default cost
+    Argon { cost: ArgonCost, len: usize, charset: Charset },
+    /// `pin_len<N>` digits only, or `pin_len<N>_safe` skipping trivial PINs
+    Pin { len: usize, safe: bool },
+    /// `lesspass`: LessPass v2 with the entry's [`LessPassProfile`]
+    LessPass,
+    /// `spectre`: Spectre / Master Password v3 with the entry's [`SpectreProfile`]
+    Spectre,
+    /// `sgp_<md5|sha512>_len<N>`: SuperGenPass
+    Sgp { hash: SgpHash, len: usize },
+    /// `pwdhash`: Stanford PwdHash
+    PwdHash,
+    /// `bip39_words<N>`: English BIP39 mnemonic of 12, 15, 18, 21 or 24 words
+    Bip39 { words: usize },
+    /// `words<N>_<dash|dot|space|underscore>[_cap]`: N words from the EFF large wordlist,
+    /// optionally capitalized
+    Words { words: usize, sep: WordSep, cap: bool },
+}
+
+fn parse_num<T: FromStr>(part: &str, prefix: &str) -> Option<T> { part.strip_prefix(prefix)?.parse().ok() }
+
+impl MethodSpec {
+    fn validate(&self) -> Result<(), GenError> {
+        let len = match *self {
+            MethodSpec::LegacyV1 | MethodSpec::LegacyV2 | MethodSpec::LessPass | MethodSpec::Spectre | MethodSpec::PwdHash => {
+                return Ok(())
+            }
+            MethodSpec::Sgp { len, .. } => {
+                if !(SGP_MIN_LEN..=SGP_MAX_LEN).contains(&len) {
+                    return Err(GenError::InvalidParams(format!("supergenpass length must be {}..={}", SGP_MIN_LEN, SGP_MAX_LEN)));
+                }
+                return Ok(());
+            }
+            MethodSpec::Len { len, .. } | MethodSpec::V3 { len, .. } | MethodSpec::Argon { len, .. } => len,
+            MethodSpec::Bip39 { words } => {
+                if !BIP39_WORD_COUNTS.contains(&words) {
+                    return Err(GenError::InvalidParams("bip39 mnemonics have 12, 15, 18, 21 or 24 words".into()));
+                }
+                return Ok(());
+            }
+            MethodSpec::Words { words, .. } => {
+                if !(WORDS_MIN..=WORDS_MAX).contains(&words) {
+                    return Err(GenError::InvalidParams(format!("passphrases have {}..={} words", WORDS_MIN, WORDS_MAX)));
+                }
+                return Ok(());
+            }
+            MethodSpec::Pin { len, .. } => {
+                if !(PIN_MIN_LEN..=MAX_LEN).contains(&len) {
+                    return Err(GenError::InvalidParams(format!("PIN length must be {}..={}", PIN_MIN_LEN, MAX_LEN)));
+                }
+                return Ok(());
+            }
+        };
+        if len == 0 || len > MAX_LEN {
+            return Err(GenError::InvalidParams(format!("length must be 1..={}", MAX_LEN)));
+        }
+        if let MethodSpec::Argon { cost, .. } = self {
+            if cost.mem_kib > ARGON_GEN_MAX_MEM_KIB || cost.iterations > ARGON_GEN_MAX_ITERATIONS || cost.parallelism > ARGON_GEN_MAX_PARALLELISM {
+                return Err(GenError::InvalidParams("argon2 cost above supported maximum".into()));
+            }
+            self.argon_params(len * 2)?;
+        }
+        Ok(())
+    }
+
+    fn argon_params(&self, out_len: usize) -> Result<Params, GenError> {
+        let MethodSpec::Argon { cost, .. } = self else { return Err(GenError::InvalidParams("not an argon method".into())) };
+        Params::new(cost.mem_kib, cost.iterations, cost.parallelism, Some(out_len))
+            .map_err(|e| GenError::InvalidParams(e.to_string()))
+    }
+}
+
+impl FromStr for MethodSpec {
+    type Err = GenError;
+
+    fn from_str(id: &str) -> Result<Self, GenError> {
+        let unknown = || GenError::UnknownMethod(id.to_string());
+        let parts: Vec<&str> = id.split('_').collect();
+        let charset = |s: &str| Charset::parse(s).ok_or_else(unknown);
+        let len = |s: &str| parse_num::<usize>(s, "len").ok_or_else(unknown);
+        let words = |s: &str| parse_num::<usize>(s, "words").ok_or_else(unknown);
+        let sep = |s: &str| WordSep::parse(s).ok_or_else(unknown);
+        let spec = match parts.as_slice() {
+            ["legacy", "v1"] => MethodSpec::LegacyV1,
+            ["legacy", "v2"] => MethodSpec::LegacyV2,
+            ["lesspass"] => MethodSpec::LessPass,
+            ["spectre"] => MethodSpec::Spectre,
+            ["pwdhash"] => MethodSpec::PwdHash,
+            ["pin", l] => MethodSpec::Pin { len: len(l)?, safe: false },
+            ["pin", l, "safe"] => MethodSpec::Pin { len: len(l)?, safe: true },
+            ["bip39", w] => MethodSpec::Bip39 { words: parse_num(w, "words").ok_or_else(unknown)? },
+            [w, s] if w.starts_with("words") => MethodSpec::Words { words: words(w)?, sep: sep(s)?, cap: false },
+            [w, s, "cap"] if w.starts_with("words") => MethodSpec::Words { words: words(w)?, sep: sep(s)?, cap: true },
+            [l, cs] => MethodSpec::Len { len: len(l)?, charset: charset(cs)? },
+            ["v3", l, cs] => MethodSpec::V3 { len: len(l)?, charset: charset(cs)? },
+            ["argon", l, cs] => MethodSpec::Argon { cost: ArgonCost::default(), len: len(l)?, charset: charset(cs)? },
+            ["argon", m, t, p, l, cs] => {
+                let cost = ArgonCost {
+                    mem_kib: parse_num(m, "m").ok_or_else(unknown)?,
+                    iterations: parse_num(t, "t").ok_or_else(unknown)?,
+                    parallelism: parse_num(p, "p").ok_or_else(unknown)?,
+                };
+                MethodSpec::Argon { cost, len: len(l)?, charset: charset(cs)? }
+            }
+            ["sgp", h, l] => MethodSpec::Sgp { hash: SgpHash::parse(h).ok_or_else(unknown)?, len: len(l)? },
+            _ => return Err(unknown()),
+        };
+        spec.validate()?;
+        // Reject alternate spellings ("len036_strong", explicit default argon cost, ...)
+        if spec.to_string() != id { return Err(unknown()); }
+        Ok(spec)
+    }
+}
+
+impl fmt::Display for MethodSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MethodSpec::LegacyV1 => f.write_str("legacy_v1"),
+            MethodSpec::LegacyV2 => f.write_str("legacy_v2"),
+            MethodSpec::Len { len, charset } => write!(f, "len{}_{}", len, charset.as_str()),
+            MethodSpec::V3 { len, charset } => write!(f, "v3_len{}_{}", len, charset.as_str()),
+            MethodSpec::Argon { cost, len, charset } if *cost == ArgonCost::default() => {
+                write!(f, "argon_len{}_{}", len, charset.as_str())
+            }
+            MethodSpec::Argon { cost, len, charset } => write!(
+                f,
+                "argon_m{}_t{}_p{}_len{}_{}",
+                cost.mem_kib, cost.iterations, cost.parallelism, len, charset.as_str()
+            ),
+            MethodSpec::Pin { len, safe: false } => write!(f, "pin_len{}", len),
+            MethodSpec::Pin { len, safe: true } => write!(f, "pin_len{}_safe", len),
+            MethodSpec::LessPass => f.write_str("lesspass"),
+            MethodSpec::Spectre => f.write_str("spectre"),
+            MethodSpec::Sgp { hash, len } => write!(f, "sgp_{}_len{}", hash.as_str(), len),
+            MethodSpec::PwdHash => f.write_str("pwdhash"),
+            MethodSpec::Bip39 { words } => write!(f, "bip39_words{}", words),
+            MethodSpec::Words { words, sep, cap } => write!(f, "words{}_{}{}", words, sep.as_str(), if *cap { "_cap" } else { "" }),
+        }
+    }
+}
+
+/// Per-entry inputs that refine a method without changing its id.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GenOptions<'a> {
+    pub policy: Option<&'a Policy>,
+    /// Rotation counter; 0 derives exactly what the method did before counters existed
+    pub counter: u32,
+    /// Login, length, rules and counter for `lesspass`; `None` means the LessPass defaults
+    pub lesspass: Option<&'a LessPassProfile>,
+    /// Full name, site counter and template for `spectre` (required there)
+    pub spectre: Option<&'a SpectreProfile>,
+}
+
+impl MethodSpec {
+    fn supports_counter(&self) -> bool { matches!(self, MethodSpec::V3 { .. } | MethodSpec::Argon { .. } | MethodSpec::Pin { .. }) }
+}
+
+pub fn generate(master: &str, postfix: &str, method_id: &str) -> Result<String, GenError> {
+    generate_with(master, postfix, method_id, &GenOptions::default())
+}
+
+pub fn generate_with(master: &str, postfix: &str, method_id: &str, opts: &GenOptions) -> Result<String, GenError> {
+    let spec = check(method_id, opts)?;
+    match opts.policy {
+        None => derive(master, postfix, &spec, opts),
+        Some(policy) => {
+            let rules = policy.resolve(&spec)?;
+            let key = method_stream(master, postfix, &spec, opts.counter, 32)?;
+            rules.generate(&key)
+        }
+    }
+}
+
+/// What a method emits and how strong it is, for the method picker and audits.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct MethodInfo {
+    /// Canonical method id
+    pub id: String,
+    /// Characters the output is drawn from; empty for word-based methods
+    pub alphabet: String,
+    /// Number of distinct symbols: characters, or words for BIP39 and EFF passphrases
+    pub alphabet_size: usize,
+    /// Output length in symbols; `None` when it depends on the master (PwdHash)
+    pub length: Option<usize>,
+    /// log2 of the number of equally likely outputs, capped by the underlying key material.
+    /// For PwdHash this is the cap, an upper bound.
+    pub entropy_bits: f64,
+    /// Underlying construction, e.g. `HMAC-SHA256` or `Argon2id (m=19456 KiB, t=2, p=1)`
+    pub primitive: String,
+    /// Kept for compatibility only: fast unsalted hashes or MD5
+    pub deprecated: bool,
+    /// Output is a secret on its own (a wallet seed), not a site password
+    pub high_value: bool,
+    pub supports_counter: bool,
+}
+
+/// Describes `method_id` as it would generate with `opts`: a policy narrows length and
+/// alphabet, LessPass and Spectre profiles pick theirs (defaults when absent).
+pub fn method_info(method_id: &str, opts: &GenOptions) -> Result<MethodInfo, GenError> {
+    let spec: MethodSpec = method_id.parse()?;
+    // an entry without a spectre profile still has a template to describe: the default one
+    if spec != MethodSpec::Spectre || opts.spectre.is_some() { check(method_id, opts)?; }
+    let chars = |set: &[u8]| String::from_utf8_lossy(set).into_owned();
+    let log2 = |n: usize| (n as f64).log2();
+    let (alphabet, length, bits, cap, primitive) = match spec {
+        MethodSpec::LegacyV1 => (chars(BASE64_ALPHABET), Some(22), 22.0 * 6.0, MD5_BITS, "MD5(master || postfix)".to_string()),
+        // 43 base64 characters and the `.` that replaces the padding
+        MethodSpec::LegacyV2 => (chars(URL_BASE64_ALPHABET), Some(44), 43.0 * 6.0, KEY_BITS, "SHA-256(master || postfix)".to_string()),
+        MethodSpec::Len { len, charset } | MethodSpec::V3 { len, charset } | MethodSpec::Argon { len, charset, .. } => {
+            let primitive = match spec {
+                MethodSpec::Len { .. } => "SHA-256 chain over master::postfix::method".to_string(),
+                MethodSpec::V3 { .. } => "HMAC-SHA256".to_string(),
+                _ => {
+                    let cost = spec.argon_params(len * 2)?;
+                    format!("Argon2id (m={} KiB, t={}, p={})", cost.m_cost(), cost.t_cost(), cost.p_cost())
+                }
+            };
+            match opts.policy {
+                Some(policy) => {
+                    let rules = policy.resolve(&spec)?;
+                    let required: Vec<usize> = rules.required.iter().map(Vec::len).collect();
+                    (chars(&rules.alphabet), Some(rules.len), constrained_bits(rules.alphabet.len(), rules.len, &required), KEY_BITS, primitive)
+                }
+                None => (chars(charset.alphabet()), Some(len), len as f64 * log2(charset.alphabet().len()), KEY_BITS, primitive),
+            }
+        }
+        MethodSpec::Pin { len, safe } => {
+            let all = 10f64.powi(len as i32);
+            // all-same digits, +1/-1 runs, and the 200 years 1900-2099 for 4 digits
+            let trivial = 10 + 2 * 11usize.saturating_sub(len) + if len == PIN_MIN_LEN { 200 } else { 0 };
+            let outputs = if safe { all - trivial as f64 } else { all };
+            (chars(DIGITS), Some(len), outputs.log2(), KEY_BITS, "HMAC-SHA256".to_string())
+        }
+        MethodSpec::LessPass => {
+            let profile = opts.lesspass.cloned().unwrap_or_default();
+            let rules = profile.rules();
+            let all = rules.concat();
+            // free characters from the union, then one character of every rule
+            let bits = (profile.length - rules.len()) as f64 * log2(all.len()) + rules.iter().map(|set| log2(set.len())).sum::<f64>();
+            (chars(&all), Some(profile.length), bits, KEY_BITS, format!("PBKDF2-HMAC-SHA256 ({} iterations)", LESSPASS_ITERATIONS))
+        }
+        MethodSpec::Spectre => {
+            let template = opts.spectre.map(|p| p.template).unwrap_or_default();
+            let mut alphabet = Vec::new();
+            for &class in template.patterns().iter().flat_map(|p| p.as_bytes()) {
+                for &c in spectre_class(class) { if !alphabet.contains(&c) { alphabet.push(c); } }
+            }
+            let outputs: f64 = template.patterns().iter()
+                .map(|p| p.bytes().map(|class| spectre_class(class).len() as f64).product::<f64>())
+                .sum();
+            let length = template.patterns().iter().map(|p| p.len()).max().unwrap_or(0);
+            (chars(&alphabet), Some(length), outputs.log2(), KEY_BITS, format!("scrypt (N=2^{}, r={}, p={}) + HMAC-SHA256", SPECTRE_LOG_N, SPECTRE_R, SPECTRE_P))
+        }
+        MethodSpec::Sgp { hash, len } => {
+            // a lowercase first character, then at least one uppercase letter and one digit
+            let bits = log2(26) + constrained_bits(62, len - 1, &[26, 10]);
+            let (cap, name) = match hash { SgpHash::Md5 => (MD5_BITS, "MD5"), SgpHash::Sha512 => (512.0, "SHA-512") };
+            (chars(ALNUM_ALPHABET), Some(len), bits, cap, format!("{}, iterated (SuperGenPass)", name))
+        }
+        // the length follows the master's, so only the MD5 bound is known
+        MethodSpec::PwdHash => (chars(BASE64_ALPHABET), None, MD5_BITS, MD5_BITS, "HMAC-MD5 (PwdHash)".to_string()),
+        // the checksum adds no entropy: 32 bits per 3 words
+        MethodSpec::Bip39 { words } => (String::new(), Some(words), (words * 32 / 3) as f64, KEY_BITS, "HMAC-SHA256 + BIP39 checksum".to_string()),
+        // separator and capitals are fixed by the id, so every word is worth log2(7776) bits
+        MethodSpec::Words { words, .. } => (String::new(), Some(words), words as f64 * log2(EFF_WORD_COUNT), KEY_BITS, "HMAC-SHA256 + EFF wordlist".to_string()),
+    };
+    let alphabet_size = match spec {
+        MethodSpec::Bip39 { .. } => BIP39_WORDLIST.lines().count(),
+        MethodSpec::Words { .. } => EFF_WORD_COUNT,
+        _ => alphabet.len(),
+    };
+    Ok(MethodInfo {
+        id: spec.to_string(),
+        alphabet,
+        alphabet_size,
+        length,
+        entropy_bits: bits.min(cap),
+        primitive,
+        deprecated: matches!(spec, MethodSpec::LegacyV1 | MethodSpec::LegacyV2 | MethodSpec::Sgp { hash: SgpHash::Md5, .. } | MethodSpec::PwdHash),
+        high_value: matches!(spec, MethodSpec::Bip39 { .. }),
+        supports_counter: spec.supports_counter(),
+    })
+}
+
+/// log2 of the number of `len`-character strings over `alphabet` symbols that contain at least
+/// one symbol of every disjoint `required` class (inclusion-exclusion, as fractions to stay finite).
+fn constrained_bits(alphabet: usize, len: usize, required: &[usize]) -> f64 {
+    let a = alphabet as f64;
+    let fraction: f64 = (0..1u32 << required.len())
+        .map(|mask| {
+            let missing: usize = required.iter().enumerate().filter(|(i, _)| mask >> i & 1 == 1).map(|(_, n)| n).sum();
+            let sign = if mask.count_ones() % 2 == 0 { 1.0 } else { -1.0 };
+            sign * ((a - missing as f64) / a).powi(len as i32)
+        })
+        .sum();
+    len as f64 * a.log2() + fraction.log2()
+}
+
+/// Parses `method_id` and checks that `opts` can be applied to it, without deriving anything.
+pub fn check(method_id: &str, opts: &GenOptions) -> Result<MethodSpec, GenError> {
+    let spec: MethodSpec = method_id.parse()?;
+    if opts.counter != 0 && !spec.supports_counter() {
+        return Err(GenError::InvalidParams("rotation counters need a v3, argon or pin method".into()));
+    }
+    if let Some(policy) = opts.policy { policy.resolve(&spec)?; }
+    if let Some(profile) = opts.lesspass {
+        if spec != MethodSpec::LessPass {
+            return Err(GenError::InvalidParams("lesspass profiles need the lesspass method".into()));
+        }
+        profile.validate()?;
+    }
+    match (spec, opts.spectre) {
+        (MethodSpec::Spectre, Some(profile)) => profile.validate()?,
+        (MethodSpec::Spectre, None) => return Err(GenError::InvalidParams("spectre needs a full name".into())),
+        (_, Some(_)) => return Err(GenError::InvalidParams("spectre profiles need the spectre method".into())),
+        (_, None) => {}
+    }
+    Ok(spec)
+}
+
+fn derive(master: &str, postfix: &str, spec: &MethodSpec, opts: &GenOptions) -> Result<String, GenError> {
+    let counter = opts.counter;
+    let out = match *spec {
         // Legacy formats compatible with references/password-store/manager.py
-        "legacy_v1" => {
+        MethodSpec::LegacyV1 => {
             let mut md5_ctx = md5::Context::new();
             md5_ctx.consume(master.as_bytes());
             md5_ctx.consume(postfix.as_bytes());
             let digest = md5_ctx.compute();
             b64_no_pad(&digest.0)
         }
-        "legacy_v2" => {
+        MethodSpec::LegacyV2 => {
             let mut h = Sha256::new();
             h.update(master.as_bytes());
             h.update(postfix.as_bytes());
@@ -54,33 +578,642 @@ pub fn generate(master: &str, postfix: &str, method_id: &str) -> String {
             let s = STANDARD.encode(digest);
             s.replace('=', ".").replace('+', "-").replace('/', "_")
         }
-        // New deterministic length-limited methods
-        id if id.starts_with("len") => {
-            let parts: Vec<&str> = id.split('_').collect();
-            let len: usize = parts.get(0).and_then(|v| v.strip_prefix("len")).and_then(|n| n.parse().ok()).unwrap_or(36);
-            let strong = parts.get(1).map(|s| *s).unwrap_or("alnum");
-            let alnum = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
-            let strong_alphabet = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789!@#$%^&*()-_=+[]{};:,.?/<>~";
-            let alphabet: &[u8] = if strong == "strong" { strong_alphabet } else { alnum };
+        MethodSpec::Len { len, charset } | MethodSpec::V3 { len, charset } | MethodSpec::Argon { len, charset, .. } => {
+            let stream = method_stream(master, postfix, spec, counter, len * 2)?;
+            map_to_alphabet(stream, charset.alphabet(), len)
+        }
+        // Consecutive PIN-sized chunks of one digit stream; `_safe` moves on past trivial
+        // chunks, so it matches the plain method whenever the first PIN is not trivial
+        MethodSpec::Pin { len, safe } => {
+            let stream = method_stream(master, postfix, spec, counter, len * PIN_CANDIDATES * 2)?;
+            let digits = map_to_alphabet(stream, DIGITS, len * PIN_CANDIDATES);
+            let pin = digits.as_bytes().chunks(len).find(|c| !safe || !is_trivial_pin(c))
+                .ok_or_else(|| GenError::InvalidParams("no non-trivial PIN found".into()))?;
+            String::from_utf8_lossy(pin).into_owned()
+        }
+        // Compatibility with LessPass v2; the postfix is the LessPass "site"
+        MethodSpec::LessPass => opts.lesspass.cloned().unwrap_or_default().render(master, postfix)?,
+        // Compatibility with Spectre / Master Password v3; the postfix is the site name
+        MethodSpec::Spectre => {
+            let profile = opts.spectre.ok_or_else(|| GenError::InvalidParams("spectre needs a full name".into()))?;
+            profile.render(master, postfix)?
+        }
+        // Bookmarklet compatibility; both hash the registrable domain of the postfix
+        MethodSpec::Sgp { hash, len } => supergenpass(master, &reduce_domain(postfix), hash, len)?,
+        MethodSpec::PwdHash => pwdhash(master, &reduce_domain(postfix))?,
+        // 4 bytes of entropy per 3 words: 128 bits for 12 words, 256 for 24
+        MethodSpec::Bip39 { words } => bip39_mnemonic(&method_stream(master, postfix, spec, counter, words * 4 / 3)?),
+        // two bytes per word plus headroom for rejected draws
+        MethodSpec::Words { words, sep, cap } => eff_passphrase(method_stream(master, postfix, spec, counter, words * 4)?, words, sep, cap),
+    };
+    Ok(out)
+}
+
+// Host part of a URL or hostname reduced to its registrable domain the way SuperGenPass and
+// PwdHash do: last two labels, or three under "co.uk"-style country second levels. IPs are kept.
+fn reduce_domain(postfix: &str) -> String {
+    let host = postfix.trim().to_lowercase();
+    let host = host.split_once("://").map_or(host.as_str(), |(_, rest)| rest);
+    let host = host.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = host.rsplit('@').next().unwrap_or_default();
+    let host = host.split(':').next().unwrap_or_default().trim_matches('.');
+    let labels: Vec<&str> = host.split('.').collect();
+    if labels.len() == 4 && labels.iter().all(|l| l.parse::<u8>().is_ok()) { return host.to_string(); }
+    let keep = match labels.as_slice() {
+        [.., sld, tld] if labels.len() >= 3 && BOOKMARKLET_SUFFIXES.lines().any(|s| s == format!("{}.{}", sld, tld)) => 3,
+        _ => 2,
+    };
+    labels[labels.len().saturating_sub(keep)..].join(".")
+}
+
+// SuperGenPass: hash `master:domain` with its base64 variant (+ -> 9, / -> 8, = -> A) at least
+// 10 times, until the first `len` characters start lowercase and contain an uppercase and a digit
+fn supergenpass(master: &str, domain: &str, hash: SgpHash, len: usize) -> Result<String, GenError> {
+    let valid = |p: &str| {
+        p.starts_with(|c: char| c.is_ascii_lowercase())
+            && p.contains(|c: char| c.is_ascii_uppercase())
+            && p.contains(|c: char| c.is_ascii_digit())
+    };
+    let mut h = format!("{}:{}", master, domain);
+    for round in 0..SGP_MAX_ROUNDS {
+        if round >= SGP_MIN_ROUNDS && valid(&h[..len]) { return Ok(h[..len].to_string()); }
+        let digest = match hash {
+            SgpHash::Md5 => md5::compute(h.as_bytes()).0.to_vec(),
+            SgpHash::Sha512 => Sha512::digest(h.as_bytes()).to_vec(),
+        };
+        h = STANDARD.encode(digest).replace('+', "9").replace('/', "8").replace('=', "A");
+    }
+    Err(GenError::InvalidParams("supergenpass found no valid password".into()))
+}
+
+fn hmac_md5(key: &[u8], msg: &[u8]) -> [u8; 16] {
+    let mut block = [0u8; 64];
+    if key.len() > 64 { block[..16].copy_from_slice(&md5::compute(key).0) } else { block[..key.len()].copy_from_slice(key) }
+    let mut inner = md5::Context::new();
+    inner.consume(block.map(|b| b ^ 0x36));
+    inner.consume(msg);
+    let mut outer = md5::Context::new();
+    outer.consume(block.map(|b| b ^ 0x5c));
+    outer.consume(inner.compute().0);
+    outer.compute().0
+}
+
+// PwdHash: base64 HMAC-MD5(master, domain) cut to the master's length + 2, then its
+// character-class fixups and rotation, following pwdhash.js. Like its md5.js, only the low
+// byte of each UTF-16 unit is hashed.
+fn pwdhash(master: &str, domain: &str) -> Result<String, GenError> {
+    let low_bytes = |s: &str| -> Vec<u8> { s.encode_utf16().map(|u| u as u8).collect() };
+    let hash = b64_no_pad(&hmac_md5(&low_bytes(master), &low_bytes(domain)));
+    let non_word = |b: &u8| !(b.is_ascii_alphanumeric() || *b == b'_');
+    let nonalphanumeric = master.chars().any(|c| !(c.is_ascii_alphanumeric() || c == '_'));
+
+    let start = (master.encode_utf16().count() + 2).saturating_sub(4).min(hash.len());
+    let mut out = hash.as_bytes()[..start].to_vec();
+    let mut extras = hash.as_bytes()[start..].iter().copied();
+    let mut next = || extras.next().unwrap_or(0);
+    let c = if out.iter().any(u8::is_ascii_uppercase) { next() } else { b'A' + next() % 26 };
+    out.push(c);
+    let c = if out.iter().any(u8::is_ascii_lowercase) { next() } else { b'a' + next() % 26 };
+    out.push(c);
+    let c = if out.iter().any(u8::is_ascii_digit) { next() } else { b'0' + next() % 10 };
+    out.push(c);
+    let c = if out.iter().any(non_word) && nonalphanumeric { next() } else { b'+' };
+    out.push(c);
+    if !nonalphanumeric {
+        while let Some(pos) = out.iter().position(non_word) { out[pos] = b'A' + next() % 26; }
+    }
+    let by = usize::from(next()) % out.len();
+    out.rotate_left(by);
+    // pwdhash.js pads with NUL once the hash runs out, which happens for long masters
+    if out.contains(&0) { return Err(GenError::InvalidParams("master is too long for pwdhash".into())); }
+    Ok(String::from_utf8_lossy(&out).into_owned())
+}
+
+// BIP39: the entropy followed by the first ENT/32 bits of its SHA-256, read as 11-bit word indices
+fn bip39_mnemonic(entropy: &[u8]) -> String {
+    let words: Vec<&str> = BIP39_WORDLIST.lines().collect();
+    let mut bits = entropy.to_vec();
+    bits.push(sha256(entropy)[0]);
+    let bit = |i: usize| usize::from(bits[i / 8] >> (7 - i % 8) & 1);
+    let count = (entropy.len() * 8 + entropy.len() / 4) / 11;
+    (0..count)
+        .map(|w| words[(0..11).fold(0, |idx, j| idx << 1 | bit(w * 11 + j))])
+        .collect::<Vec<_>>()
+        .join(" ")
+}
 
+// EFF words: big-endian u16 draws below 8 * 7776 pick a word, the rest are rejected so every
+// word is equally likely; the stream is extended if it runs out
+fn eff_passphrase(mut stream: Vec<u8>, count: usize, sep: WordSep, cap: bool) -> String {
+    let list: Vec<&str> = EFF_WORDLIST.lines().filter_map(|l| l.split_once('\t').map(|(_, w)| w)).collect();
+    let limit = (usize::from(u16::MAX) + 1) / EFF_WORD_COUNT * EFF_WORD_COUNT;
+    let mut words = Vec::with_capacity(count);
+    let mut idx = 0;
+    while words.len() < count {
+        if idx + 2 > stream.len() { stream = extend_stream(stream.clone(), stream.len() + 32); }
+        let v = usize::from(u16::from_be_bytes([stream[idx], stream[idx + 1]]));
+        idx += 2;
+        if v >= limit { continue; }
+        let word = list[v % EFF_WORD_COUNT];
+        words.push(if cap { word[..1].to_ascii_uppercase() + &word[1..] } else { word.to_string() });
+    }
+    words.join(sep.separator())
+}
+
+// All-same digits, a +1/-1 run (1234, 8765) or, for 4 digits, a year 1900-2099
+fn is_trivial_pin(pin: &[u8]) -> bool {
+    let steps: Vec<i16> = pin.windows(2).map(|w| i16::from(w[1]) - i16::from(w[0])).collect();
+    let run = steps.iter().all(|&d| d == steps[0] && d.abs() <= 1);
+    run || (pin.len() == 4 && matches!(&pin[..2], b"19" | b"20"))
+}
+
+// First `need` bytes of the keystream behind a stream-based method. A non-zero counter
+// becomes one more framed field, so counter 0 keeps the original outputs.
+fn method_stream(master: &str, postfix: &str, spec: &MethodSpec, counter: u32, need: usize) -> Result<Vec<u8>, GenError> {
+    let counter_be = counter.to_be_bytes();
+    // Native constructions see NFC master and postfix; `len*` keeps hashing the raw bytes
+    let (master_nfc, postfix_nfc) = (nfc(master), nfc(postfix));
+    match *spec {
+        MethodSpec::LegacyV1 | MethodSpec::LegacyV2 | MethodSpec::LessPass | MethodSpec::Spectre | MethodSpec::Sgp { .. } | MethodSpec::PwdHash => {
+            Err(GenError::InvalidParams(format!("{} has a fixed output format", spec)))
+        }
+        // Deterministic length-limited methods; the seed embeds the (canonical) method id
+        MethodSpec::Len { .. } => {
             let mut seed = Vec::new();
             seed.extend_from_slice(master.as_bytes());
             seed.extend_from_slice(b"::");
             seed.extend_from_slice(postfix.as_bytes());
             seed.extend_from_slice(b"::");
-            seed.extend_from_slice(id.as_bytes());
+            seed.extend_from_slice(spec.to_string().as_bytes());
 
             let digest = sha256(&seed);
-            let stream = extend_stream(digest.to_vec(), len * 2);
-            map_to_alphabet(stream, alphabet, len)
+            Ok(extend_stream(digest.to_vec(), need))
+        }
+        // v3: HMAC-SHA256 keyed with the master over a length-prefixed frame of
+        // postfix and parameters
+        MethodSpec::V3 { len, charset } => {
+            let len_be = (len as u32).to_be_bytes();
+            let mut fields: Vec<&[u8]> = vec![V3_TAG, postfix_nfc.as_bytes(), charset.as_str().as_bytes(), &len_be];
+            if counter != 0 { fields.push(&counter_be); }
+            let msg = frame(&fields);
+            Ok(hmac_stream(master_nfc.as_bytes(), &msg, need))
+        }
+        // Argon2id-stretched: every guess at the master costs one full Argon2id run
+        MethodSpec::Argon { len, charset, .. } => {
+            let len_be = (len as u32).to_be_bytes();
+            let mut fields: Vec<&[u8]> = vec![ARGON_TAG, postfix_nfc.as_bytes(), charset.as_str().as_bytes(), &len_be];
+            if counter != 0 { fields.push(&counter_be); }
+            let salt = frame(&fields);
+            let mut stream = vec![0u8; need];
+            let alg = Argon2::new(Algorithm::Argon2id, Version::V0x13, spec.argon_params(need)?);
+            alg.hash_password_into(master_nfc.as_bytes(), &salt, &mut stream)
+                .map_err(|e| GenError::InvalidParams(e.to_string()))?;
+            Ok(stream)
+        }
+        // PINs: same construction as v3 with its own tag; `_safe` is not framed so both
+        // spellings share one digit stream
+        MethodSpec::Pin { len, .. } => {
+            let len_be = (len as u32).to_be_bytes();
+            let mut fields: Vec<&[u8]> = vec![PIN_TAG, postfix_nfc.as_bytes(), &len_be];
+            if counter != 0 { fields.push(&counter_be); }
+            Ok(hmac_stream(master_nfc.as_bytes(), &frame(&fields), need))
+        }
+        // BIP39 entropy: same construction under its own tag, keyed by the word count
+        MethodSpec::Bip39 { words } => {
+            let words_be = (words as u32).to_be_bytes();
+            Ok(hmac_stream(master_nfc.as_bytes(), &frame(&[BIP39_TAG, postfix_nfc.as_bytes(), &words_be]), need))
+        }
+        // EFF passphrases: keyed by the word count only, so separator and `_cap` variants
+        // spell the same words
+        MethodSpec::Words { words, .. } => {
+            let words_be = (words as u32).to_be_bytes();
+            Ok(hmac_stream(master_nfc.as_bytes(), &frame(&[WORDS_TAG, postfix_nfc.as_bytes(), &words_be]), need))
+        }
+    }
+}
+
+/// Site rules layered on top of a stream-based method: the method still picks the
+/// key material and default length/charset, the policy narrows what may be emitted.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Policy {
+    #[serde(default)]
+    pub min_len: Option<usize>,
+    #[serde(default)]
+    pub max_len: Option<usize>,
+    #[serde(default)]
+    pub require_lower: bool,
+    #[serde(default)]
+    pub require_upper: bool,
+    #[serde(default)]
+    pub require_digit: bool,
+    #[serde(default)]
+    pub require_symbol: bool,
+    /// Allowed symbols, replacing the default set (ASCII punctuation only)
+    #[serde(default)]
+    pub symbols: Option<String>,
+    /// Characters that must never appear
+    #[serde(default)]
+    pub forbidden: String,
+}
+
+struct PolicyRules { alphabet: Vec<u8>, len: usize, required: Vec<Vec<u8>> }
+
+impl Policy {
+    fn resolve(&self, spec: &MethodSpec) -> Result<PolicyRules, GenError> {
+        let invalid = |m: &str| GenError::InvalidParams(m.to_string());
+        let (len, charset) = match *spec {
+            MethodSpec::Len { len, charset } | MethodSpec::V3 { len, charset } | MethodSpec::Argon { len, charset, .. } => (len, charset),
+            MethodSpec::LegacyV1
+            | MethodSpec::LegacyV2
+            | MethodSpec::Pin { .. }
+            | MethodSpec::LessPass
+            | MethodSpec::Spectre
+            | MethodSpec::Sgp { .. }
+            | MethodSpec::PwdHash
+            | MethodSpec::Bip39 { .. }
+            | MethodSpec::Words { .. } => {
+                return Err(invalid("policies need a len, v3 or argon method"))
+            }
+        };
+        let min = self.min_len.unwrap_or(1).max(1);
+        let max = self.max_len.unwrap_or(MAX_LEN).min(MAX_LEN);
+        if min > max { return Err(invalid("policy min length exceeds max length")); }
+
+        let mut symbols: Vec<u8> = Vec::new();
+        for b in self.symbols.as_deref().map(str::as_bytes).unwrap_or(SYMBOLS) {
+            if !b.is_ascii_punctuation() { return Err(invalid("policy symbols must be ASCII punctuation")); }
+            if !symbols.contains(b) { symbols.push(*b); }
+        }
+        if charset == Charset::Alnum && !self.require_symbol { symbols.clear(); }
+
+        let keep = |set: &[u8]| -> Vec<u8> { set.iter().copied().filter(|b| !self.forbidden.as_bytes().contains(b)).collect() };
+        let classes = [
+            (keep(LOWER), self.require_lower, "lowercase"),
+            (keep(UPPER), self.require_upper, "uppercase"),
+            (keep(DIGITS), self.require_digit, "digit"),
+            (keep(&symbols), self.require_symbol, "symbol"),
+        ];
+        let mut alphabet = Vec::new();
+        let mut required = Vec::new();
+        for (set, needed, name) in classes {
+            if needed {
+                if set.is_empty() { return Err(GenError::InvalidParams(format!("policy allows no {} characters", name))); }
+                required.push(set.clone());
+            }
+            alphabet.extend_from_slice(&set);
+        }
+        if alphabet.is_empty() { return Err(invalid("policy forbids every character")); }
+        let len = len.clamp(min, max);
+        if len < required.len() { return Err(invalid("policy length is shorter than its required classes")); }
+        Ok(PolicyRules { alphabet, len, required })
+    }
+}
+
+impl PolicyRules {
+    // Generate-and-test over a keyed candidate sequence. Candidates depend only on the
+    // alphabet and length, so adding a requirement the password already meets keeps it.
+    fn generate(&self, key: &[u8]) -> Result<String, GenError> {
+        let len_be = (self.len as u32).to_be_bytes();
+        for attempt in 0..POLICY_MAX_ATTEMPTS {
+            let msg = frame(&[POLICY_TAG, &self.alphabet, &len_be, &attempt.to_be_bytes()]);
+            let candidate = map_to_alphabet(hmac_stream(key, &msg, self.len * 2), &self.alphabet, self.len);
+            if self.required.iter().all(|set| candidate.bytes().any(|b| set.contains(&b))) {
+                return Ok(candidate);
+            }
+        }
+        Err(GenError::InvalidParams("policy could not be satisfied".into()))
+    }
+}
+
+/// LessPass v2 password profile (site = entry postfix). Missing fields take the
+/// LessPass defaults: no login, 16 characters, all four rules, counter 1.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct LessPassProfile {
+    pub login: String,
+    pub length: usize,
+    pub counter: u32,
+    pub lowercase: bool,
+    pub uppercase: bool,
+    pub digits: bool,
+    pub symbols: bool,
+}
+
+impl Default for LessPassProfile {
+    fn default() -> Self {
+        LessPassProfile { login: String::new(), length: 16, counter: 1, lowercase: true, uppercase: true, digits: true, symbols: true }
+    }
+}
+
+impl LessPassProfile {
+    fn rules(&self) -> Vec<&'static [u8]> {
+        [(self.lowercase, LOWER), (self.uppercase, UPPER), (self.digits, DIGITS), (self.symbols, LESSPASS_SYMBOLS)]
+            .into_iter().filter(|(on, _)| *on).map(|(_, set)| set).collect()
+    }
+
+    fn validate(&self) -> Result<(), GenError> {
+        if self.rules().is_empty() {
+            return Err(GenError::InvalidParams("lesspass needs at least one character rule".into()));
+        }
+        if !(LESSPASS_MIN_LEN..=LESSPASS_MAX_LEN).contains(&self.length) {
+            return Err(GenError::InvalidParams(format!("lesspass length must be {}..={}", LESSPASS_MIN_LEN, LESSPASS_MAX_LEN)));
+        }
+        Ok(())
+    }
+
+    // LessPass renderPassword: the PBKDF2 output is one big-endian integer that is
+    // repeatedly divided by the set size, one remainder per character; then one
+    // character per rule is inserted at positions drawn the same way.
+    fn render(&self, master: &str, site: &str) -> Result<String, GenError> {
+        self.validate()?;
+        let salt = format!("{}{}{:x}", site, self.login, self.counter);
+        let mut entropy = [0u8; 32];
+        pbkdf2_hmac::<Sha256>(master.as_bytes(), salt.as_bytes(), LESSPASS_ITERATIONS, &mut entropy);
+
+        let rules = self.rules();
+        let all: Vec<u8> = rules.concat();
+        let mut out: Vec<u8> = (0..self.length - rules.len())
+            .map(|_| all[divmod(&mut entropy, all.len() as u32) as usize])
+            .collect();
+        let extra: Vec<u8> = rules.iter().map(|set| set[divmod(&mut entropy, set.len() as u32) as usize]).collect();
+        for c in extra {
+            let at = divmod(&mut entropy, out.len() as u32) as usize;
+            out.insert(at, c);
         }
-        _ => {
-            // Fallback to default strong 36
-            generate(master, postfix, "len36_strong")
+        Ok(String::from_utf8_lossy(&out).into_owned())
+    }
+}
+
+/// Spectre password templates; `Long` is the Spectre default for passwords.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SpectreTemplate { Maximum, #[default] Long, Medium, Short, Basic, Pin, Name, Phrase }
+
+impl SpectreTemplate {
+    fn patterns(self) -> &'static [&'static str] {
+        match self {
+            SpectreTemplate::Maximum => &["anoxxxxxxxxxxxxxxxxx", "axxxxxxxxxxxxxxxxxno"],
+            SpectreTemplate::Long => &[
+                "CvcvnoCvcvCvcv", "CvcvCvcvnoCvcv", "CvcvCvcvCvcvno", "CvccnoCvcvCvcv", "CvccCvcvnoCvcv",
+                "CvccCvcvCvcvno", "CvcvnoCvccCvcv", "CvcvCvccnoCvcv", "CvcvCvccCvcvno", "CvcvnoCvcvCvcc",
+                "CvcvCvcvnoCvcc", "CvcvCvcvCvccno", "CvccnoCvccCvcv", "CvccCvccnoCvcv", "CvccCvccCvcvno",
+                "CvcvnoCvccCvcc", "CvcvCvccnoCvcc", "CvcvCvccCvccno", "CvccnoCvcvCvcc", "CvccCvcvnoCvcc",
+                "CvccCvcvCvccno",
+            ],
+            SpectreTemplate::Medium => &["CvcnoCvc", "CvcCvcno"],
+            SpectreTemplate::Short => &["Cvcn"],
+            SpectreTemplate::Basic => &["aaanaaan", "aannaaan", "aaannaaa"],
+            SpectreTemplate::Pin => &["nnnn"],
+            SpectreTemplate::Name => &["cvccvcvcv"],
+            SpectreTemplate::Phrase => &["cvcc cvc cvccvcv cvc", "cvc cvccvcvcv cvcv", "cv cvccv cvc cvcvccv"],
+        }
+    }
+}
+
+fn spectre_class(class: u8) -> &'static [u8] {
+    match class {
+        b'V' => b"AEIOU",
+        b'C' => b"BCDFGHJKLMNPQRSTVWXYZ",
+        b'v' => b"aeiou",
+        b'c' => b"bcdfghjklmnpqrstvwxyz",
+        b'A' => b"AEIOUBCDFGHJKLMNPQRSTVWXYZ",
+        b'a' => b"AEIOUaeiouBCDFGHJKLMNPQRSTVWXYZbcdfghjklmnpqrstvwxyz",
+        b'n' => b"0123456789",
+        b'o' => b"@&%?,=[]_:-+*$#!'^~;()/.",
+        b'x' => b"AEIOUaeiouBCDFGHJKLMNPQRSTVWXYZbcdfghjklmnpqrstvwxyz0123456789!@#$%^&*()",
+        _ => b" ",
+    }
+}
+
+/// Spectre / Master Password v3 site profile (site name = entry postfix). The full name
+/// is part of the scrypt salt, so it must match what was typed into Spectre exactly.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct SpectreProfile {
+    pub full_name: String,
+    pub counter: u32,
+    pub template: SpectreTemplate,
+}
+
+impl Default for SpectreProfile {
+    fn default() -> Self { SpectreProfile { full_name: String::new(), counter: 1, template: SpectreTemplate::Long } }
+}
+
+impl SpectreProfile {
+    fn validate(&self) -> Result<(), GenError> {
+        if self.full_name.is_empty() { return Err(GenError::InvalidParams("spectre needs a full name".into())); }
+        if self.counter == 0 { return Err(GenError::InvalidParams("spectre site counters start at 1".into())); }
+        Ok(())
+    }
+
+    // master key = scrypt(master, scope || len(name) || name); site key = HMAC-SHA256(master key,
+    // scope || len(site) || site || counter); byte 0 picks the pattern, byte i+1 character i.
+    fn render(&self, master: &str, site: &str) -> Result<String, GenError> {
+        self.validate()?;
+        Ok(self.site_password(&self.master_key(master)?, site))
+    }
+
+    fn master_key(&self, master: &str) -> Result<[u8; 64], GenError> {
+        let name = self.full_name.as_bytes();
+        let salt = [SPECTRE_SCOPE, &(name.len() as u32).to_be_bytes(), name].concat();
+        let params = ScryptParams::new(SPECTRE_LOG_N, SPECTRE_R, SPECTRE_P, 64).map_err(|e| GenError::InvalidParams(e.to_string()))?;
+        let mut key = [0u8; 64];
+        scrypt(master.as_bytes(), &salt, &params, &mut key).map_err(|e| GenError::InvalidParams(e.to_string()))?;
+        Ok(key)
+    }
+
+    fn site_password(&self, key: &[u8; 64], site: &str) -> String {
+        let mut mac = HmacSha256::new_from_slice(key).expect("hmac accepts keys of any length");
+        mac.update(SPECTRE_SCOPE);
+        mac.update(&(site.len() as u32).to_be_bytes());
+        mac.update(site.as_bytes());
+        mac.update(&self.counter.to_be_bytes());
+        let seed = mac.finalize().into_bytes();
+
+        let patterns = self.template.patterns();
+        let pattern = patterns[seed[0] as usize % patterns.len()];
+        pattern.bytes().zip(&seed[1..]).map(|(class, &b)| {
+            let set = spectre_class(class);
+            set[b as usize % set.len()] as char
+        }).collect()
+    }
+}
+
+// Divides a big-endian integer in place by a small divisor, returning the remainder
+fn divmod(n: &mut [u8], d: u32) -> u32 {
+    let mut rem = 0u32;
+    for b in n.iter_mut() {
+        let cur = (rem << 8) | u32::from(*b);
+        *b = (cur / d) as u8;
+        rem = cur % d;
+    }
+    rem
+}
+
+/// 32-byte Ed25519 secret seed for `postfix`: the v3 construction under its own tag,
+/// so an SSH key never shares bytes with any password derived for the same postfix.
+pub fn ssh_ed25519_seed(master: &str, postfix: &str, counter: u32) -> [u8; 32] {
+    let counter_be = counter.to_be_bytes();
+    let postfix = nfc(postfix);
+    let mut fields: Vec<&[u8]> = vec![SSH_ED25519_TAG, postfix.as_bytes()];
+    if counter != 0 { fields.push(&counter_be); }
+    let mut seed = [0u8; 32];
+    seed.copy_from_slice(&hmac_stream(nfc(master).as_bytes(), &frame(&fields), 32));
+    seed
+}
+
+/// Key sealing the OTP secrets stored with this master's entries (see `otp::SealedOtp`).
+pub fn otp_key(master: &str) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    key.copy_from_slice(&hmac_stream(nfc(master).as_bytes(), &frame(&[OTP_KEY_TAG]), 32));
+    key
+}
+
+// `syllables` consonant-vowel pairs then `digits` digits, each drawn by rejection sampling
+// from HMAC(master, frame(fields)); the entry counter does not take part, so rotating
+// a password keeps the account name and security answers.
+fn pronounceable(master: &str, fields: &[&[u8]], syllables: usize, digits: usize) -> String {
+    let mut stream = hmac_stream(nfc(master).as_bytes(), &frame(fields), 64);
+    let mut idx = 0;
+    let mut pick = |alphabet: &[u8]| loop {
+        if idx >= stream.len() { stream = extend_stream(stream.clone(), stream.len() + 32); }
+        let v = u32::from(stream[idx]); idx += 1;
+        let m = alphabet.len() as u32;
+        if v < (u32::from(u8::MAX) / m) * m { return alphabet[(v % m) as usize] as char }
+    };
+    let mut out = String::with_capacity(syllables * 2 + digits);
+    for _ in 0..syllables {
+        out.push(pick(CONSONANTS));
+        out.push(pick(VOWELS));
+    }
+    for _ in 0..digits { out.push(pick(DIGITS)); }
+    out
+}
+
+/// Pronounceable username for `postfix`, e.g. `kivodare27`.
+pub fn username(master: &str, postfix: &str) -> String {
+    pronounceable(master, &[USERNAME_TAG, nfc(postfix).as_bytes()], USERNAME_SYLLABLES, USERNAME_DIGITS)
+}
+
+/// Email address for `postfix`: `template` with its `{alias}` replaced by a pronounceable
+/// local-part that is independent of `username`, e.g. `me+{alias}@example.com`.
+pub fn email_alias(master: &str, postfix: &str, template: &str) -> Result<String, GenError> {
+    check_alias_template(template)?;
+    Ok(template.replace(ALIAS_PLACEHOLDER, &pronounceable(master, &[ALIAS_TAG, nfc(postfix).as_bytes()], ALIAS_SYLLABLES, 0)))
+}
+
+/// A template needs exactly one `{alias}`, inside the local-part, and a non-empty domain.
+pub fn check_alias_template(template: &str) -> Result<(), GenError> {
+    let bad = |why: &str| Err(GenError::InvalidParams(format!("alias template {}", why)));
+    if template.chars().any(char::is_whitespace) { return bad("must not contain spaces"); }
+    let Some((local, domain)) = template.split_once('@') else { return bad("needs an @domain") };
+    if domain.is_empty() || domain.contains('@') { return bad("needs exactly one @domain"); }
+    if local.matches(ALIAS_PLACEHOLDER).count() != 1 || domain.contains(ALIAS_PLACEHOLDER) {
+        return bad("needs exactly one {alias} before the @");
+    }
+    Ok(())
+}
+
+/// How a security answer is rendered: syllables (`nivuzitojuna`) or BIP39 words (`crisp tail timber route`).
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AnswerStyle { #[default] Pronounceable, Words }
+
+/// A security question kept on an entry. Only the question is stored; the answer is
+/// derived again from master, postfix and question every time it is shown.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SecurityQuestion {
+    pub question: String,
+    #[serde(default)]
+    pub style: AnswerStyle,
+}
+
+// Case, spacing and Unicode form of the question do not matter, so retyping it gives the same answer
+fn canonical_question(question: &str) -> String {
+    let folded = question.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    nfc(&folded).into_owned()
+}
+
+/// Answer for `question` on the entry with `postfix`: 6 syllables (~38 bits) or 4 words (44 bits)
+/// keyed by HMAC(master, frame("saforia/answer", postfix, question)).
+pub fn security_answer(master: &str, postfix: &str, question: &SecurityQuestion) -> String {
+    let q = canonical_question(&question.question);
+    let postfix = nfc(postfix);
+    let fields: [&[u8]; 3] = [ANSWER_TAG, postfix.as_bytes(), q.as_bytes()];
+    match question.style {
+        AnswerStyle::Pronounceable => pronounceable(master, &fields, ANSWER_SYLLABLES, 0),
+        AnswerStyle::Words => {
+            let words: Vec<&str> = BIP39_WORDLIST.lines().collect();
+            // 2048 words divide 2^16 evenly, so 11 bits of each big-endian pair need no rejection
+            hmac_stream(nfc(master).as_bytes(), &frame(&fields), ANSWER_WORDS * 2)
+                .chunks(2)
+                .map(|pair| words[usize::from(u16::from_be_bytes([pair[0], pair[1]]) & 0x7ff)])
+                .collect::<Vec<_>>()
+                .join(" ")
         }
     }
 }
 
+/// Questions must be non-empty and distinct once case and spacing are ignored.
+pub fn check_security_questions(questions: &[SecurityQuestion]) -> Result<(), GenError> {
+    let mut seen = std::collections::HashSet::new();
+    for q in questions {
+        let c = canonical_question(&q.question);
+        if c.is_empty() { return Err(GenError::InvalidParams("security question is empty".into())); }
+        if !seen.insert(c) { return Err(GenError::InvalidParams(format!("duplicate security question: {}", q.question.trim()))); }
+    }
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct KnownAnswers {
+    version: u32,
+    vectors: Vec<KnownAnswer>,
+}
+
+#[derive(Deserialize)]
+struct KnownAnswer {
+    master: String,
+    postfix: String,
+    method: String,
+    #[serde(default)]
+    counter: u32,
+    #[serde(default)]
+    policy: Option<Policy>,
+    #[serde(default)]
+    lesspass: Option<LessPassProfile>,
+    #[serde(default)]
+    spectre: Option<SpectreProfile>,
+    expected: String,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct SelfTestReport {
+    /// Version of the embedded corpus
+    pub version: u32,
+    pub vectors: usize,
+    /// One line per vector whose output differs (or errors); empty when the build is sound
+    pub failures: Vec<String>,
+}
+
+/// Regenerates every embedded known-answer vector. A failure means this build would derive
+/// different passwords than the release the vectors were recorded with.
+pub fn self_test() -> SelfTestReport {
+    let corpus: KnownAnswers = serde_json::from_str(KNOWN_ANSWERS).expect("embedded known_answers.json is valid");
+    let failures = corpus.vectors.iter()
+        .filter_map(|v| {
+            let opts = GenOptions { policy: v.policy.as_ref(), counter: v.counter, lesspass: v.lesspass.as_ref(), spectre: v.spectre.as_ref() };
+            let got = generate_with(&v.master, &v.postfix, &v.method, &opts);
+            match got {
+                Ok(got) if got == v.expected => None,
+                Ok(got) => Some(format!("{} {:?} (counter {}): expected {:?}, got {:?}", v.method, v.postfix, v.counter, v.expected, got)),
+                Err(e) => Some(format!("{} {:?} (counter {}): {}", v.method, v.postfix, v.counter, e)),
+            }
+        })
+        .collect();
+    SelfTestReport { version: corpus.version, vectors: corpus.vectors.len(), failures }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -109,7 +1242,484 @@ mod tests {
         let v2 = STANDARD.encode(digest).replace('=', ".").replace('+', "-").replace('/', "_");
         assert_eq!(v2, "zPDy_Q9fcmjfyqASE-dmT74bRTokBz_MHoqZdX5owbk.");
 
-        assert_eq!(generate(master, postfix, "legacy_v1"), v1);
-        assert_eq!(generate(master, postfix, "legacy_v2"), v2);
+        assert_eq!(generate(master, postfix, "legacy_v1").unwrap(), v1);
+        assert_eq!(generate(master, postfix, "legacy_v2").unwrap(), v2);
+    }
+
+    // Same vectors as scripts/legacy-parity.mjs (independent Node implementation)
+    #[test]
+    fn v3_known_answers() {
+        assert_eq!(generate("test", "example", "v3_len20_strong").unwrap(), "Y/M@:1F$lX;?p(F=?cJ&");
+        assert_eq!(generate("test", "example", "v3_len36_alnum").unwrap(), "1kmAXiknFuCFbUiWXK5giTSvMNuw0ksi2vB9");
+        assert_eq!(generate("ab", "c", "v3_len20_strong").unwrap(), "ug.ZvwB/U@?[[39>~<oy");
+        assert_eq!(generate("a", "bc", "v3_len20_strong").unwrap(), "DUyZ3?(_DLrr,8j9ux{V");
+    }
+
+    #[test]
+    fn v3_framing_has_no_boundary_collisions() {
+        // Shifting bytes between master and postfix collides for the older families
+        assert_eq!(generate("ab", "c", "legacy_v2").unwrap(), generate("a", "bc", "legacy_v2").unwrap());
+        assert_ne!(generate("ab", "c", "v3_len20_strong").unwrap(), generate("a", "bc", "v3_len20_strong").unwrap());
+        // A postfix carrying separators or parameters cannot mimic another entry
+        assert_ne!(generate("m", "x::len20_strong", "v3_len20_strong").unwrap(), generate("m", "x", "v3_len20_strong").unwrap());
+        // Parameters are framed too, so a shorter output is not a prefix of a longer one
+        assert!(!generate("m", "x", "v3_len36_strong").unwrap().starts_with(&generate("m", "x", "v3_len20_strong").unwrap()));
+    }
+
+    // Cross-checked against an independent Argon2id implementation
+    #[test]
+    fn argon_known_answers() {
+        assert_eq!(generate("test", "example", "argon_len24_strong").unwrap(), "+-4.TXTe(NU{ygJ=FBiIpw1-");
+        assert_eq!(generate("test", "example", "argon_m1024_t1_p1_len24_strong").unwrap(), "avq%~j*,}RCn8aLuwUz=7.BI");
+        assert_eq!(generate("test", "example", "argon_m1024_t1_p1_len20_alnum").unwrap(), "VGAwNUR5bD91Mia4bMIz");
+    }
+
+    #[test]
+    fn argon_cost_changes_output() {
+        assert_ne!(generate("m", "site", "argon_m1024_t1_p1_len24_strong").unwrap(), generate("m", "site", "argon_m2048_t1_p1_len24_strong").unwrap());
+    }
+
+    #[test]
+    fn argon_rejects_unbounded_params() {
+        assert!("argon_m4194304_t1_p1_len24_strong".parse::<MethodSpec>().is_err());
+        assert!("argon_m1024_t100_p1_len24_strong".parse::<MethodSpec>().is_err());
+        assert!("argon_m4_t1_p1_len24_strong".parse::<MethodSpec>().is_err());
+        assert!("argon_m1024_t1_p1_strong".parse::<MethodSpec>().is_err());
+    }
+
+    #[test]
+    fn method_spec_round_trips() {
+        for id in [
+            "legacy_v1", "legacy_v2", "len10_alnum", "len36_strong", "v3_len20_strong",
+            "argon_len24_strong", "argon_m1024_t1_p1_len24_alnum",
+        ] {
+            let spec: MethodSpec = id.parse().unwrap();
+            assert_eq!(spec.to_string(), id);
+        }
+        assert_eq!(
+            "argon_len24_strong".parse::<MethodSpec>().unwrap(),
+            MethodSpec::Argon { cost: ArgonCost::default(), len: 24, charset: Charset::Strong }
+        );
+    }
+
+    #[test]
+    fn unknown_or_non_canonical_ids_are_errors() {
+        for id in [
+            "", "len36", "len36_strnog", "len036_strong", "len0_alnum", "len999_alnum",
+            "legacy_v3", "v4_len20_strong", "v3_len20_strong_x", "argon_m19456_t2_p1_len24_strong",
+        ] {
+            assert!(generate("m", "p", id).is_err(), "{id} should not generate");
+        }
+        assert_eq!(generate("m", "p", "len36_strnog"), Err(GenError::UnknownMethod("len36_strnog".into())));
+    }
+
+    fn with_policy(policy: &Policy) -> GenOptions<'_> { GenOptions { policy: Some(policy), ..Default::default() } }
+
+    fn check_policy(method_id: &str, policy: &Policy) -> Result<MethodSpec, GenError> { check(method_id, &with_policy(policy)) }
+
+    fn picky_site() -> Policy {
+        Policy { max_len: Some(16), require_digit: true, require_symbol: true, forbidden: "<>{}".into(), ..Default::default() }
+    }
+
+    #[test]
+    fn policy_output_satisfies_rules() {
+        let pw = generate_with("test", "example", "len36_strong", &with_policy(&picky_site())).unwrap();
+        assert_eq!(pw, "2Q7;FnD$t3v]EyCZ");
+        assert_eq!(pw.len(), 16);
+        assert!(!pw.contains(['<', '>', '{', '}']));
+        assert!(pw.bytes().any(|b| b.is_ascii_digit()));
+        assert!(pw.bytes().any(|b| SYMBOLS.contains(&b)));
+        assert_eq!(generate_with("test", "example", "len36_strong", &with_policy(&picky_site())).unwrap(), pw);
+    }
+
+    #[test]
+    fn policy_requirement_already_met_keeps_password() {
+        let stricter = Policy { require_upper: true, ..picky_site() };
+        assert_eq!(
+            generate_with("test", "example", "len36_strong", &with_policy(&stricter)).unwrap(),
+            generate_with("test", "example", "len36_strong", &with_policy(&picky_site())).unwrap()
+        );
+    }
+
+    #[test]
+    fn policy_symbol_set_and_min_len_on_alnum_method() {
+        let p = Policy { min_len: Some(12), require_symbol: true, symbols: Some("!".into()), ..Default::default() };
+        let pw = generate_with("m", "bank", "v3_len10_alnum", &with_policy(&p)).unwrap();
+        assert_eq!(pw.len(), 12);
+        assert!(pw.contains('!'));
+        assert!(pw.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'!'));
+    }
+
+    #[test]
+    fn policy_rejects_impossible_rules() {
+        let no_digits = Policy { require_digit: true, forbidden: "0123456789".into(), ..Default::default() };
+        assert!(check_policy("len20_strong", &no_digits).is_err());
+        assert!(check_policy("legacy_v2", &Policy::default()).is_err());
+        assert!(check_policy("len20_strong", &Policy { min_len: Some(30), max_len: Some(20), ..Default::default() }).is_err());
+        assert!(check_policy("len20_strong", &Policy { symbols: Some("é".into()), ..Default::default() }).is_err());
+        assert!(check_policy("len20_strong", &picky_site()).is_ok());
+    }
+
+    #[test]
+    fn counter_zero_keeps_outputs_and_bumps_rotate() {
+        let at = |counter| generate_with("test", "example", "v3_len20_strong", &GenOptions { counter, ..Default::default() }).unwrap();
+        assert_eq!(at(0), "Y/M@:1F$lX;?p(F=?cJ&");
+        assert_eq!(at(1), "SN4mBP;x.,L%~!n0S1IY");
+        assert_ne!(at(1), at(2));
+        let argon = |counter| generate_with("test", "example", "argon_m1024_t1_p1_len24_strong", &GenOptions { counter, ..Default::default() }).unwrap();
+        assert_eq!(argon(0), "avq%~j*,}RCn8aLuwUz=7.BI");
+        assert_eq!(argon(1), "^%8_Cr9CR{g{Jo^j$(&XsVbR");
+    }
+
+    #[test]
+    fn counter_needs_a_framed_method() {
+        let bumped = GenOptions { counter: 1, ..Default::default() };
+        assert!(generate_with("m", "p", "len36_strong", &bumped).is_err());
+        assert!(generate_with("m", "p", "legacy_v1", &bumped).is_err());
+        assert!(check("v3_len20_strong", &bumped).is_ok());
+    }
+
+    #[test]
+    fn pin_known_answers() {
+        assert_eq!(generate("test", "example", "pin_len4").unwrap(), "7362");
+        assert_eq!(generate("test", "example", "pin_len6").unwrap(), "818790");
+        assert_eq!(generate("test", "example", "pin_len8_safe").unwrap(), "71112578");
+        let bumped = GenOptions { counter: 1, ..Default::default() };
+        assert_eq!(generate_with("test", "example", "pin_len6", &bumped).unwrap(), "521869");
+    }
+
+    #[test]
+    fn safe_pin_skips_trivial_pins_only() {
+        // "site27" happens to start its digit stream with a year
+        assert_eq!(generate("test", "site27", "pin_len4").unwrap(), "2025");
+        assert_eq!(generate("test", "site27", "pin_len4_safe").unwrap(), "9423");
+        assert_eq!(generate("test", "example", "pin_len4_safe").unwrap(), "7362");
+        for pin in ["0000", "1234", "9876", "1987", "2001", "456789"] {
+            assert!(is_trivial_pin(pin.as_bytes()), "{pin}");
+        }
+        for pin in ["7362", "1235", "2101", "1122", "135790"] {
+            assert!(!is_trivial_pin(pin.as_bytes()), "{pin}");
+        }
+    }
+
+    #[test]
+    fn pin_ids_and_limits() {
+        assert_eq!("pin_len6_safe".parse::<MethodSpec>().unwrap(), MethodSpec::Pin { len: 6, safe: true });
+        for id in ["pin_len3", "pin_len0", "pin_len6_unsafe", "pin6", "pin_len06"] {
+            assert!(generate("m", "p", id).is_err(), "{id} should not generate");
+        }
+        assert!(check_policy("pin_len6", &Policy::default()).is_err());
+    }
+
+    // Vectors from the LessPass v2 specification (site example.org, login contact@example.org)
+    #[test]
+    fn lesspass_known_answers() {
+        let at = |profile: LessPassProfile| {
+            let profile = LessPassProfile { login: "contact@example.org".into(), ..profile };
+            generate_with("password", "example.org", "lesspass", &GenOptions { lesspass: Some(&profile), ..Default::default() }).unwrap()
+        };
+        assert_eq!(at(LessPassProfile::default()), "WHLpUL)e00[iHR+w");
+        assert_eq!(at(LessPassProfile { length: 14, counter: 2, symbols: false, ..Default::default() }), "MBAsB7b1Prt8Sl");
+        assert_eq!(
+            at(LessPassProfile { length: 6, counter: 3, lowercase: false, uppercase: false, symbols: false, ..Default::default() }),
+            "117843"
+        );
+    }
+
+    #[test]
+    fn lesspass_profile_checks() {
+        let lesspass = |profile: &LessPassProfile, id: &str| check(id, &GenOptions { lesspass: Some(profile), ..Default::default() }).map(|_| ());
+        assert!(lesspass(&LessPassProfile::default(), "lesspass").is_ok());
+        assert!(lesspass(&LessPassProfile::default(), "v3_len20_strong").is_err());
+        assert!(lesspass(&LessPassProfile { length: 36, ..Default::default() }, "lesspass").is_err());
+        let no_rules = LessPassProfile { lowercase: false, uppercase: false, digits: false, symbols: false, ..Default::default() };
+        assert!(lesspass(&no_rules, "lesspass").is_err());
+        assert!(check_policy("lesspass", &Policy::default()).is_err());
+        let profile: LessPassProfile = serde_json::from_str(r#"{"login":"me","digits":false}"#).unwrap();
+        assert_eq!(profile, LessPassProfile { login: "me".into(), digits: false, ..Default::default() });
+    }
+
+    // Published Master Password v3 test vectors for "Robert Lee Mitchell" / "banana colored duckling"
+    #[test]
+    fn spectre_known_answers() {
+        let profile = SpectreProfile { full_name: "Robert Lee Mitchell".into(), ..Default::default() };
+        let opts = GenOptions { spectre: Some(&profile), ..Default::default() };
+        assert_eq!(generate_with("banana colored duckling", "masterpasswordapp.com", "spectre", &opts).unwrap(), "Jejr5[RepuSosp");
+
+        // scrypt is slow in debug builds, so derive the master key once for the template vectors
+        let key = profile.master_key("banana colored duckling").unwrap();
+        let key_id: String = sha256(&key).iter().map(|b| format!("{:02X}", b)).collect();
+        assert_eq!(key_id, "98EEF4D1DF46D849574A82A03C3177056B15DFFCA29BB3899DE4628453675302");
+        let at = |site: &str, counter, template| SpectreProfile { counter, template, ..profile.clone() }.site_password(&key, site);
+        assert_eq!(at("masterpasswordapp.com", 1, SpectreTemplate::Long), "Jejr5[RepuSosp");
+        assert_eq!(at("masterpasswordapp.com", 1, SpectreTemplate::Maximum), "W6@692^B1#&@gVdSdLZ@");
+        assert_eq!(at("masterpasswordapp.com", 1, SpectreTemplate::Medium), "Jej2$Quv");
+        assert_eq!(at("masterpasswordapp.com", 1, SpectreTemplate::Basic), "WAo2xIg6");
+        assert_eq!(at("masterpasswordapp.com", 1, SpectreTemplate::Short), "Jej2");
+        assert_eq!(at("masterpasswordapp.com", 1, SpectreTemplate::Pin), "7662");
+        assert_eq!(at("masterpasswordapp.com", 1, SpectreTemplate::Name), "jejraquvo");
+        assert_eq!(at("masterpasswordapp.com", 1, SpectreTemplate::Phrase), "jejr quv cabsibu tam");
+        assert_eq!(at("masterpasswordapp.com", 2, SpectreTemplate::Long), "GornJuci5/Zafs");
+        assert_eq!(at("twitter.com", 1, SpectreTemplate::Long), "PozoLalv0_Yelo");
+    }
+
+    #[test]
+    fn spectre_needs_a_profile() {
+        assert!(generate("m", "site", "spectre").is_err());
+        let profile = SpectreProfile { full_name: "A".into(), ..Default::default() };
+        let opts = GenOptions { spectre: Some(&profile), ..Default::default() };
+        assert!(check("spectre", &opts).is_ok());
+        assert!(check("v3_len20_strong", &opts).is_err());
+        let zero = SpectreProfile { counter: 0, ..profile.clone() };
+        assert!(check("spectre", &GenOptions { spectre: Some(&zero), ..Default::default() }).is_err());
+        let profile: SpectreProfile = serde_json::from_str(r#"{"full_name":"A","template":"phrase"}"#).unwrap();
+        assert_eq!(profile, SpectreProfile { full_name: "A".into(), counter: 1, template: SpectreTemplate::Phrase });
+    }
+
+    // Cross-checked against independent ports of supergenpass-lib and pwdhash.js
+    #[test]
+    fn bookmarklet_known_answers() {
+        assert_eq!(generate("test", "example.com", "sgp_md5_len10").unwrap(), "w9UbG0NEk7");
+        assert_eq!(generate("test", "https://www.example.com/login", "sgp_md5_len10").unwrap(), "w9UbG0NEk7");
+        assert_eq!(generate("test", "example.com", "sgp_md5_len4").unwrap(), "w9Ub");
+        assert_eq!(generate("test", "example.com", "sgp_sha512_len24").unwrap(), "sJfoZg3nU8y32EyHFRlSY08u");
+        assert_eq!(generate("test", "google.com", "sgp_md5_len10").unwrap(), "w8Me8P6pXA");
+        assert_eq!(generate("test", "example.com", "pwdhash").unwrap(), "MeJ8G9");
+        assert_eq!(generate("my53cret#", "https://www.example.com/", "pwdhash").unwrap(), "Bu6aSm+Zcsf");
+        assert_eq!(generate("password", "example.co.uk", "pwdhash").unwrap(), "FRKW3CpwXv");
+        assert_eq!(generate(&"x".repeat(40), "example.com", "pwdhash").unwrap(), "70LT0e5azwi6FS7634ovbgAAAA");
+        assert!(generate(&format!("{}#", "x".repeat(39)), "example.com", "pwdhash").is_err());
+        assert!(generate("test", "example.com", "sgp_md5_len25").is_err());
+        assert!(generate("test", "example.com", "sgp_sha1_len10").is_err());
+    }
+
+    #[test]
+    fn domains_reduce_like_the_bookmarklets() {
+        assert_eq!(reduce_domain("https://www.google.com/search?q=x"), "google.com");
+        assert_eq!(reduce_domain("login.example.co.uk"), "example.co.uk");
+        assert_eq!(reduce_domain("shop.example.com.au"), "example.com.au");
+        // the bookmarklets' own suffix list, not a guess from label shapes
+        assert_eq!(reduce_domain("www.bmf.gv.at"), "bmf.gv.at");
+        assert_eq!(reduce_domain("shop.acme.ltd.uk"), "acme.ltd.uk");
+        assert_eq!(reduce_domain("mail.example.br.com"), "example.br.com");
+        assert_eq!(reduce_domain("www.example.gov.uk"), "gov.uk");
+        assert_eq!(reduce_domain("www.example.com.de"), "com.de");
+        assert_eq!(BOOKMARKLET_SUFFIXES.lines().count(), 270);
+        assert_eq!(reduce_domain("https://user@mail.example.org:8443/"), "example.org");
+        assert_eq!(reduce_domain("192.168.1.10:8080/x"), "192.168.1.10");
+        assert_eq!(reduce_domain("localhost"), "localhost");
+    }
+
+    #[test]
+    fn bip39_encoding_matches_reference_vectors() {
+        // from the BIP39 reference test vectors (trezor/python-mnemonic)
+        let cases = [
+            ("00000000000000000000000000000000", "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"),
+            ("7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f", "legal winner thank year wave sausage worth useful legal winner thank yellow"),
+            ("80808080808080808080808080808080", "letter advice cage absurd amount doctor acoustic avoid letter advice cage above"),
+            ("ffffffffffffffffffffffffffffffff", "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo wrong"),
+            ("9e885d952ad362caeb4efe34a8e91bd2", "ozone drill grab fiber curtain grace pudding thank cruise elder eight picnic"),
+            ("000000000000000000000000000000000000000000000000", "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon agent"),
+            ("ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff", "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote"),
+        ];
+        for (hex, words) in cases {
+            let entropy: Vec<u8> = (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap()).collect();
+            assert_eq!(bip39_mnemonic(&entropy), words);
+        }
+        assert_eq!(BIP39_WORDLIST.lines().count(), 2048);
+    }
+
+    #[test]
+    fn bip39_known_answers() {
+        assert_eq!(generate("test", "example", "bip39_words12").unwrap(), "earth wait voice delay secret horse man canvas wolf have boy alone");
+        assert_eq!(generate("test", "staging", "bip39_words12").unwrap(), "tape harsh snap wrong cabbage shed crouch square film place unveil test");
+        assert_eq!(
+            generate("test", "example", "bip39_words24").unwrap(),
+            "route beyond wine fashion blouse atom spell blanket silly fat quit fit pride satisfy law very link radio aisle just renew dentist exist eye"
+        );
+        for words in BIP39_WORD_COUNTS {
+            assert_eq!(generate("test", "example", &format!("bip39_words{}", words)).unwrap().split(' ').count(), words);
+        }
+        for bad in ["bip39_words13", "bip39_words012", "bip39_words0", "bip39", "bip39_len12"] {
+            assert!(generate("test", "example", bad).is_err(), "{}", bad);
+        }
+        let counter = GenOptions { counter: 1, ..Default::default() };
+        assert!(generate_with("test", "example", "bip39_words12", &counter).is_err());
+        let policy = Policy::default();
+        assert!(generate_with("test", "example", "bip39_words12", &GenOptions { policy: Some(&policy), ..Default::default() }).is_err());
+    }
+
+    #[test]
+    fn eff_passphrases_known_answers() {
+        assert_eq!(generate("test", "example", "words6_dash").unwrap(), "marry-astute-staff-mortuary-spoils-extruding");
+        assert_eq!(generate("test", "example", "words6_space_cap").unwrap(), "Marry Astute Staff Mortuary Spoils Extruding");
+        assert_eq!(generate("test", "example", "words8_dot").unwrap(), "snare.pellet.scheme.flashily.feed.goal.galley.kinsman");
+        let list: Vec<&str> = EFF_WORDLIST.lines().filter_map(|l| l.split_once('\t').map(|(_, w)| w)).collect();
+        assert_eq!(list.len(), EFF_WORD_COUNT);
+        assert_eq!((list[0], list[EFF_WORD_COUNT - 1]), ("abacus", "zoom"));
+        for id in ["words4_dash", "words7_dot_cap", "words12_underscore", "words20_space"] {
+            let spec: MethodSpec = id.parse().unwrap();
+            assert_eq!(spec.to_string(), id);
+        }
+        for bad in ["words3_dash", "words21_dash", "words06_dash", "words6", "words6_none", "words6_dash_caps", "words6_cap_dash"] {
+            assert!(generate("test", "example", bad).is_err(), "{}", bad);
+        }
+        // draws from 8 * 7776 up are rejected instead of wrapping onto the start of the list
+        assert_eq!(eff_passphrase(vec![0xf2, 0xff], 1, WordSep::Dash, false), "zoom");
+        assert_eq!(eff_passphrase(vec![0xf3, 0x00, 0xff, 0xff, 0x00, 0x01], 1, WordSep::Dash, false), "abdomen");
+        let counter = GenOptions { counter: 1, ..Default::default() };
+        assert!(generate_with("test", "example", "words6_dash", &counter).is_err());
+    }
+
+    #[test]
+    fn usernames_and_aliases_known_answers() {
+        assert_eq!(username("test", "example"), "mumavijo67");
+        assert_eq!(username("test", "staging"), "jomegila48");
+        assert_eq!(username("test2", "example"), "nedajubi34");
+        assert_eq!(email_alias("test", "example", "me+{alias}@example.com").unwrap(), "me+febuzorole@example.com");
+        assert_eq!(email_alias("test", "staging", "{alias}@catchall.example").unwrap(), "runubajogi@catchall.example");
+        assert_eq!(email_alias("test2", "example", "{alias}@x.org").unwrap(), "metujuhabo@x.org");
+    }
+
+    #[test]
+    fn alias_templates_are_validated() {
+        for ok in ["{alias}@example.com", "me+{alias}@example.com", "me.{alias}@mail.example"] {
+            assert!(check_alias_template(ok).is_ok(), "{}", ok);
+        }
+        for bad in ["", "{alias}", "me@example.com", "{alias}@", "@{alias}.com", "{alias}{alias}@example.com", "{alias}@a@b", "me {alias}@example.com", "{alias}@{alias}.com"] {
+            assert!(check_alias_template(bad).is_err(), "{}", bad);
+            assert!(email_alias("test", "example", bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn security_answers_known_answers() {
+        let q = |question: &str, style| SecurityQuestion { question: question.into(), style };
+        assert_eq!(security_answer("test", "example", &q("Mother's maiden name", AnswerStyle::Pronounceable)), "nivuzitojuna");
+        assert_eq!(security_answer("test", "example", &q("Mother's maiden name", AnswerStyle::Words)), "crisp tail timber route");
+        // case and spacing of the question are ignored
+        assert_eq!(security_answer("test", "example", &q("  mother's   MAIDEN name ", AnswerStyle::Pronounceable)), "nivuzitojuna");
+        assert_eq!(security_answer("test", "example", &q("First pet", AnswerStyle::Pronounceable)), "savavavajoka");
+        assert_eq!(security_answer("test", "example", &q("First pet", AnswerStyle::Words)), "mobile spot siren soul");
+        assert_eq!(security_answer("test", "staging", &q("First pet", AnswerStyle::Pronounceable)), "segugusolufi");
+    }
+
+    #[test]
+    fn security_questions_are_validated() {
+        let q = |question: &str| SecurityQuestion { question: question.into(), style: AnswerStyle::default() };
+        assert!(check_security_questions(&[]).is_ok());
+        assert!(check_security_questions(&[q("First pet"), q("First school")]).is_ok());
+        assert!(check_security_questions(&[q("  ")]).is_err());
+        assert!(check_security_questions(&[q("First pet"), q("first  PET")]).is_err());
+    }
+
+    #[test]
+    fn native_methods_ignore_unicode_form() {
+        // "Crème brûlée" composed (U+00E8, U+00FB, U+00E9) and decomposed (base letter + combining mark)
+        let (composed, decomposed) = ("Cr\u{e8}me br\u{fb}l\u{e9}e", "Cre\u{300}me bru\u{302}le\u{301}e");
+        let (site, site_nfd) = ("caf\u{e9}.example", "cafe\u{301}.example");
+        assert_ne!(composed.as_bytes(), decomposed.as_bytes());
+        assert_eq!(nfc(decomposed), composed);
+        assert!(matches!(nfc("test"), Cow::Borrowed(_)));
+        assert!(matches!(nfc(composed), Cow::Borrowed(_)));
+
+        for id in ["v3_len20_strong", "pin_len6_safe", "bip39_words12", "argon_m1024_t1_p1_len16_alnum"] {
+            let expected = generate(composed, site, id).unwrap();
+            assert_eq!(generate(decomposed, site, id).unwrap(), expected, "{}", id);
+            assert_eq!(generate(composed, site_nfd, id).unwrap(), expected, "{}", id);
+            assert_eq!(generate(decomposed, site_nfd, id).unwrap(), expected, "{}", id);
+        }
+        let counter = GenOptions { counter: 2, ..Default::default() };
+        assert_eq!(generate_with(decomposed, site_nfd, "v3_len20_strong", &counter), generate_with(composed, site, "v3_len20_strong", &counter));
+        assert_eq!(ssh_ed25519_seed(decomposed, site_nfd, 0), ssh_ed25519_seed(composed, site, 0));
+        assert_eq!(otp_key(decomposed), otp_key(composed));
+        assert_eq!(username(decomposed, site_nfd), username(composed, site));
+        assert_eq!(email_alias(decomposed, site_nfd, "{alias}@x.org"), email_alias(composed, site, "{alias}@x.org"));
+        let q = |question: &str| SecurityQuestion { question: question.into(), style: AnswerStyle::Words };
+        assert_eq!(security_answer(decomposed, site_nfd, &q("Ville pr\u{e9}f\u{e9}r\u{e9}e")), security_answer(composed, site, &q("Ville pre\u{301}fe\u{301}re\u{301}e")));
+
+        // legacy and len* methods keep hashing the raw bytes, so existing outputs do not move
+        for id in ["legacy_v1", "legacy_v2", "len20_strong"] {
+            assert_ne!(generate(decomposed, site, id).unwrap(), generate(composed, site, id).unwrap(), "{}", id);
+        }
+    }
+
+    #[test]
+    fn known_answer_corpus_passes_and_covers_every_family() {
+        let report = self_test();
+        assert!(report.failures.is_empty(), "{:#?}", report.failures);
+        let corpus: KnownAnswers = serde_json::from_str(KNOWN_ANSWERS).unwrap();
+        assert_eq!(report.vectors, corpus.vectors.len());
+        let specs: Vec<MethodSpec> = corpus.vectors.iter().map(|v| v.method.parse().unwrap()).collect();
+        let family = |f: fn(&MethodSpec) -> bool| specs.iter().any(f);
+        assert!(family(|s| *s == MethodSpec::LegacyV1) && family(|s| *s == MethodSpec::LegacyV2));
+        assert!(family(|s| matches!(s, MethodSpec::Len { .. })) && family(|s| matches!(s, MethodSpec::V3 { .. })));
+        assert!(family(|s| matches!(s, MethodSpec::Argon { .. })) && family(|s| matches!(s, MethodSpec::Pin { .. })));
+        assert!(family(|s| *s == MethodSpec::LessPass) && family(|s| *s == MethodSpec::Spectre));
+        assert!(family(|s| matches!(s, MethodSpec::Sgp { .. })) && family(|s| *s == MethodSpec::PwdHash));
+        assert!(family(|s| matches!(s, MethodSpec::Bip39 { .. })) && family(|s| matches!(s, MethodSpec::Words { .. })));
+        // counters, policies and outputs long enough to need extend_stream are pinned too
+        assert!(corpus.vectors.iter().any(|v| v.counter != 0) && corpus.vectors.iter().any(|v| v.policy.is_some()));
+        assert!(corpus.vectors.iter().any(|v| v.expected.len() > 128));
+    }
+
+    #[test]
+    fn method_info_reports_entropy() {
+        let info = |id: &str| method_info(id, &GenOptions::default()).unwrap();
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        // expected values from an independent count (Python, exact integers)
+        for (id, bits, len, size) in [
+            ("legacy_v1", 128.0, Some(22), 64),
+            ("legacy_v2", 256.0, Some(44), 65),
+            ("len10_alnum", 59.54196310386875, Some(10), 62),
+            ("len20_strong", 129.51466861932795, Some(20), 89),
+            ("v3_len36_strong", 233.12640351479033, Some(36), 89),
+            ("argon_len256_strong", 256.0, Some(256), 89),
+            ("pin_len4_safe", 13.25502856981873, Some(4), 10),
+            ("pin_len6_safe", 19.931539715134814, Some(6), 10),
+            ("pin_len8", 26.575424759098897, Some(8), 10),
+            ("lesspass", 96.37787375130121, Some(16), 94),
+            ("spectre", 55.83304246583896, Some(14), 81),
+            ("sgp_md5_len10", 57.94363879991512, Some(10), 62),
+            ("pwdhash", 128.0, None, 64),
+            ("bip39_words12", 128.0, Some(12), 2048),
+            ("bip39_words24", 256.0, Some(24), 2048),
+            ("words6_dash", 77.54887502163469, Some(6), 7776),
+            ("words20_space_cap", 256.0, Some(20), 7776),
+        ] {
+            let i = info(id);
+            assert!(close(i.entropy_bits, bits), "{}: {}", id, i.entropy_bits);
+            assert_eq!((i.length, i.alphabet_size), (len, size), "{}", id);
+        }
+        assert!(info("legacy_v1").deprecated && info("pwdhash").deprecated && info("sgp_md5_len10").deprecated);
+        assert!(!info("v3_len20_strong").deprecated && !info("sgp_sha512_len10").deprecated);
+        assert!(info("bip39_words12").high_value && !info("v3_len20_strong").high_value);
+        assert!(info("pin_len4").supports_counter && !info("lesspass").supports_counter);
+        assert_eq!(info("argon_m65536_t3_p1_len24_strong").primitive, "Argon2id (m=65536 KiB, t=3, p=1)");
+        assert!(info("v3_len20_alnum").alphabet.bytes().all(|b| b.is_ascii_alphanumeric()));
+        assert!(method_info("len036_strong", &GenOptions::default()).is_err());
+    }
+
+    #[test]
+    fn method_info_follows_entry_options() {
+        let policy = Policy {
+            require_lower: true, require_upper: true, require_digit: true, require_symbol: true,
+            symbols: Some("!#".into()), forbidden: "0O".into(), ..Default::default()
+        };
+        let i = method_info("v3_len20_strong", &GenOptions { policy: Some(&policy), ..Default::default() }).unwrap();
+        assert_eq!((i.alphabet_size, i.length), (62, Some(20)));
+        assert!((i.entropy_bits - 117.95619425782715).abs() < 1e-9, "{}", i.entropy_bits);
+
+        let lesspass = LessPassProfile { length: 20, symbols: false, ..Default::default() };
+        let i = method_info("lesspass", &GenOptions { lesspass: Some(&lesspass), ..Default::default() }).unwrap();
+        assert_eq!((i.alphabet_size, i.length), (62, Some(20)));
+
+        let spectre = SpectreProfile { full_name: "Alice".into(), template: SpectreTemplate::Maximum, ..Default::default() };
+        let i = method_info("spectre", &GenOptions { spectre: Some(&spectre), ..Default::default() }).unwrap();
+        assert_eq!(i.length, Some(20));
+        assert!((i.entropy_bits - 119.49605533826892).abs() < 1e-9, "{}", i.entropy_bits);
+
+        // options the method cannot take are rejected like when generating
+        assert!(method_info("legacy_v1", &GenOptions { policy: Some(&policy), ..Default::default() }).is_err());
     }
 }
@@ -1,15 +1,20 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows release
 
+mod agent;
 mod crypto;
+mod domain;
 mod gen;
+mod otp;
 mod store;
 mod paths;
 mod security;
 mod backup;
 mod config;
 mod dialogs;
+mod ssh;
 
 use serde::Serialize;
+use std::borrow::Cow;
 use zeroize::Zeroizing;
 
 #[derive(Serialize)]
@@ -18,23 +23,61 @@ struct ApiError { message: String }
 #[tauri::command]
 fn has_master() -> bool { crypto::has_master() }
 
+/// `setup_set_master` failure; `code` is set for failures the UI answers by retrying
+#[derive(Serialize)]
+struct SetupError {
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<&'static str>,
+}
+
+/// A master that is not in Unicode NFC form (decomposed accents) is refused with code
+/// `needs_normalization` while `normalize` is `None`, so the UI can ask: `Some(true)` stores the
+/// NFC form, `Some(false)` keeps it as typed.
+/// Native methods normalize either way; legacy and compatibility methods hash what is stored.
 #[tauri::command]
-fn setup_set_master(viewer_password: String, master_password: String) -> Result<String, ApiError> {
+fn setup_set_master(viewer_password: String, master_password: String, normalize: Option<bool>) -> Result<String, SetupError> {
     let viewer = Zeroizing::new(viewer_password);
-    let master = Zeroizing::new(master_password);
-    let fp = crypto::save_master(&viewer, &master).map_err(|e| ApiError{ message: e.to_string() })?;
+    let mut master = Zeroizing::new(master_password);
+    let normalized = match gen::nfc(&master) { Cow::Owned(n) => Some(Zeroizing::new(n)), Cow::Borrowed(_) => None };
+    if let Some(n) = normalized {
+        match normalize {
+            None => return Err(SetupError {
+                message: "master password is not in Unicode NFC form; choose whether to store it normalized".into(),
+                code: Some("needs_normalization"),
+            }),
+            Some(true) => master = n,
+            Some(false) => {}
+        }
+    }
+    let fp = crypto::save_master(&viewer, &master).map_err(|e| SetupError { message: e.to_string(), code: None })?;
     let mut p = config::read_prefs();
     p.active_fingerprint = Some(fp.clone());
     let _ = config::write_prefs(&p);
     Ok(fp)
 }
 
+/// Decrypts the master filed under `fp`. One still filed under the plaintext-MD5 fingerprint
+/// of older versions is moved to its keyed fingerprint on the way, and so are entries imported
+/// under that MD5 fingerprint.
+fn load_master(viewer: &str, fp: &str) -> Result<String, ApiError> {
+    let master = crypto::load_master(viewer, fp).map_err(|e| ApiError { message: e.to_string() })?;
+    if crypto::is_legacy_fingerprint(&master, fp) {
+        crypto::migrate_legacy_fingerprint(fp, viewer, &master).map_err(|e| ApiError { message: format!("fingerprint migration failed: {}", e) })?;
+    } else {
+        // best-effort, like the rewrite of older master files
+        let _ = crypto::adopt_legacy_entries(&master, fp);
+    }
+    Ok(master)
+}
+
 #[tauri::command]
 fn master_fingerprint(viewer_password: String) -> Result<String, ApiError> {
     let viewer = Zeroizing::new(viewer_password);
     let p = config::read_prefs();
     let fp = p.active_fingerprint.clone().ok_or(ApiError{ message: "no active master".into() })?;
-    crypto::master_fingerprint(&viewer, &fp).map_err(|e| ApiError { message: e.to_string() })
+    let master = load_master(&viewer, &fp)?;
+    crypto::fingerprint(&master).map_err(|e| ApiError { message: e.to_string() })
 }
 
 #[tauri::command]
@@ -43,24 +86,177 @@ fn reveal_master(viewer_password: String, fingerprint: Option<String>) -> Result
     let fp = fingerprint
         .or_else(|| config::read_prefs().active_fingerprint)
         .ok_or(ApiError{ message: "no active master".into() })?;
-    crypto::load_master(&viewer, &fp).map_err(|e| ApiError { message: e.to_string() })
+    load_master(&viewer, &fp)
+}
+
+/// Re-encrypts a master under a new viewer password; the master and its fingerprint stay as they are.
+#[tauri::command]
+fn change_viewer_password(old_viewer_password: String, new_viewer_password: String, fingerprint: Option<String>) -> Result<(), ApiError> {
+    let (old_viewer, new_viewer) = (Zeroizing::new(old_viewer_password), Zeroizing::new(new_viewer_password));
+    if new_viewer.is_empty() { return Err(ApiError{ message: "viewer password is empty".into() }); }
+    let fp = fingerprint
+        .or_else(|| config::read_prefs().active_fingerprint)
+        .ok_or(ApiError{ message: "no active master".into() })?;
+    crypto::rekey_master(&old_viewer, &new_viewer, &fp).map_err(|e| ApiError { message: e.to_string() })
+}
+
+#[tauri::command]
+fn list_key_slots(fingerprint: Option<String>) -> Result<Vec<crypto::SlotInfo>, ApiError> {
+    let fp = fingerprint
+        .or_else(|| config::read_prefs().active_fingerprint)
+        .ok_or(ApiError{ message: "no active master".into() })?;
+    crypto::list_key_slots(&fp).map_err(|e| ApiError { message: e.to_string() })
+}
+
+/// Lets another viewer password open the master, e.g. a team member's own
+#[tauri::command]
+fn add_key_slot(viewer_password: String, label: String, new_viewer_password: String, fingerprint: Option<String>) -> Result<(), ApiError> {
+    let (viewer, new_viewer) = (Zeroizing::new(viewer_password), Zeroizing::new(new_viewer_password));
+    if new_viewer.is_empty() { return Err(ApiError{ message: "viewer password is empty".into() }); }
+    let fp = fingerprint
+        .or_else(|| config::read_prefs().active_fingerprint)
+        .ok_or(ApiError{ message: "no active master".into() })?;
+    crypto::add_key_slot(&viewer, &fp, &label, &new_viewer).map_err(|e| ApiError { message: e.to_string() })
+}
+
+#[tauri::command]
+fn remove_key_slot(viewer_password: String, label: String, fingerprint: Option<String>) -> Result<(), ApiError> {
+    let viewer = Zeroizing::new(viewer_password);
+    let fp = fingerprint
+        .or_else(|| config::read_prefs().active_fingerprint)
+        .ok_or(ApiError{ message: "no active master".into() })?;
+    crypto::remove_key_slot(&viewer, &fp, &label).map_err(|e| ApiError { message: e.to_string() })
 }
 
+/// Generates the printable recovery code of a master (replacing an earlier one); shown once.
 #[tauri::command]
-fn generate_password(viewer_password: String, postfix: String, method_id: String) -> Result<String, ApiError> {
+fn add_recovery_code(viewer_password: String, fingerprint: Option<String>) -> Result<String, ApiError> {
+    let viewer = Zeroizing::new(viewer_password);
+    let fp = fingerprint
+        .or_else(|| config::read_prefs().active_fingerprint)
+        .ok_or(ApiError{ message: "no active master".into() })?;
+    crypto::add_recovery_slot(&viewer, &fp).map(|code| code.to_string()).map_err(|e| ApiError { message: e.to_string() })
+}
+
+/// Argon2id parameters taking about `target_ms` (default 1 s) per unlock on this device
+#[tauri::command]
+async fn calibrate_kdf(target_ms: Option<u64>) -> Result<crypto::KdfParams, ApiError> {
+    let target = target_ms.map(std::time::Duration::from_millis).unwrap_or(crypto::DEFAULT_UNLOCK_TARGET);
+    tauri::async_runtime::spawn_blocking(move || crypto::calibrate_kdf(target)).await
+        .map_err(|e| ApiError { message: e.to_string() })?
+        .map_err(|e| ApiError { message: e.to_string() })
+}
+
+/// Re-wraps the key slot this viewer password opens with parameters calibrated to `target_ms`
+#[tauri::command]
+async fn upgrade_master_kdf(viewer_password: String, target_ms: Option<u64>, fingerprint: Option<String>) -> Result<crypto::KdfParams, ApiError> {
+    let viewer = Zeroizing::new(viewer_password);
+    let fp = fingerprint
+        .or_else(|| config::read_prefs().active_fingerprint)
+        .ok_or(ApiError{ message: "no active master".into() })?;
+    let target = target_ms.map(std::time::Duration::from_millis).unwrap_or(crypto::DEFAULT_UNLOCK_TARGET);
+    tauri::async_runtime::spawn_blocking(move || {
+        let params = crypto::calibrate_kdf(target)?;
+        crypto::upgrade_kdf(&viewer, &fp, params).map(|_| params)
+    }).await
+        .map_err(|e| ApiError { message: e.to_string() })?
+        .map_err(|e| ApiError { message: e.to_string() })
+}
+
+#[tauri::command]
+fn generate_password(viewer_password: String, postfix: String, method_id: String, policy: Option<gen::Policy>, lesspass: Option<gen::LessPassProfile>, spectre: Option<gen::SpectreProfile>) -> Result<String, ApiError> {
     let viewer = Zeroizing::new(viewer_password);
     let p = config::read_prefs();
     let fp = p.active_fingerprint.clone().ok_or(ApiError{ message: "no active master".into() })?;
-    let master = crypto::load_master(&viewer, &fp).map_err(|e| ApiError { message: e.to_string() })?;
-    let result = gen::generate(&master, &postfix, &method_id);
-    Ok(result)
+    let master = load_master(&viewer, &fp)?;
+    let opts = gen::GenOptions { policy: policy.as_ref(), counter: 0, lesspass: lesspass.as_ref(), spectre: spectre.as_ref() };
+    gen::generate_with(&master, &postfix, &method_id, &opts).map_err(|e| ApiError { message: e.to_string() })
+}
+
+/// Regenerates the embedded known-answer vectors off the main thread; the UI runs it at startup
+/// and warns when this build would derive different passwords.
+#[tauri::command]
+async fn self_test() -> Result<gen::SelfTestReport, ApiError> {
+    tauri::async_runtime::spawn_blocking(gen::self_test).await.map_err(|e| ApiError { message: e.to_string() })
+}
+
+/// Alphabet, length, entropy and primitive of each method with default options, for the picker.
+#[tauri::command]
+fn method_info(method_ids: Vec<String>) -> Result<Vec<gen::MethodInfo>, ApiError> {
+    method_ids.iter()
+        .map(|id| gen::method_info(id, &gen::GenOptions::default()))
+        .collect::<Result<_, _>>()
+        .map_err(|e| ApiError { message: e.to_string() })
+}
+
+/// Same for a saved entry, taking its policy and profiles into account.
+#[tauri::command]
+fn entry_method_info(id: String) -> Result<gen::MethodInfo, ApiError> {
+    let Some(entry) = store::get(&id) else { return Err(ApiError{ message: "Entry not found".into() }); };
+    gen::method_info(&entry.method_id, &entry.gen_options()).map_err(|e| ApiError { message: e.to_string() })
 }
 
 #[tauri::command]
 fn list_entries() -> Vec<store::Entry> { let p = config::read_prefs(); store::list_for_fingerprint(&p.active_fingerprint) }
 
+#[derive(Serialize)]
+struct PostfixSuggestion { canonical: Option<String>, matches: Vec<store::Entry> }
+
+/// Registrable domain of a pasted URL or hostname, and the active master's entries whose
+/// postfix reduces to the same domain. Only a suggestion: the postfix is never rewritten.
+#[tauri::command]
+fn suggest_postfix(input: String) -> PostfixSuggestion {
+    let canonical = domain::canonical_postfix(&input);
+    let matches = match &canonical {
+        Some(c) => store::list_for_fingerprint(&config::read_prefs().active_fingerprint)
+            .into_iter()
+            .filter(|e| domain::canonical_postfix(&e.postfix).as_ref() == Some(c))
+            .collect(),
+        None => Vec::new(),
+    };
+    PostfixSuggestion { canonical, matches }
+}
+
+#[tauri::command]
+fn add_entry(label: String, postfix: String, method_id: String, policy: Option<gen::Policy>, lesspass: Option<gen::LessPassProfile>, spectre: Option<gen::SpectreProfile>) -> Result<store::Entry, ApiError> {
+    let p = config::read_prefs();
+    store::add(label, postfix, method_id, policy, lesspass, spectre, &p.active_fingerprint).map_err(|e| ApiError { message: e.to_string() })
+}
+
+#[tauri::command]
+fn set_entry_policy(id: String, policy: Option<gen::Policy>) -> Result<bool, ApiError> {
+    store::set_policy(&id, policy).map_err(|e| ApiError { message: e.to_string() })
+}
+
+#[tauri::command]
+fn set_entry_lesspass(id: String, lesspass: Option<gen::LessPassProfile>) -> Result<bool, ApiError> {
+    store::set_lesspass(&id, lesspass).map_err(|e| ApiError { message: e.to_string() })
+}
+
+#[tauri::command]
+fn set_entry_spectre(id: String, spectre: Option<gen::SpectreProfile>) -> Result<bool, ApiError> {
+    store::set_spectre(&id, spectre).map_err(|e| ApiError { message: e.to_string() })
+}
+
+#[tauri::command]
+fn set_entry_security_questions(id: String, questions: Vec<gen::SecurityQuestion>) -> Result<bool, ApiError> {
+    store::set_security_questions(&id, questions).map_err(|e| ApiError { message: e.to_string() })
+}
+
+#[derive(Serialize)]
+struct SecurityAnswer { question: String, answer: String }
+
+/// Answers for the entry's stored questions; they are derived here and never written to disk.
 #[tauri::command]
-fn add_entry(label: String, postfix: String, method_id: String) -> store::Entry { let p = config::read_prefs(); store::add(label, postfix, method_id, &p.active_fingerprint) }
+fn entry_security_answers(id: String, viewer_password: String) -> Result<Vec<SecurityAnswer>, ApiError> {
+    let Some(entry) = store::get(&id) else { return Err(ApiError{ message: "Entry not found".into() }); };
+    let viewer = Zeroizing::new(viewer_password);
+    let fp = entry.fingerprint.clone().or_else(|| config::read_prefs().active_fingerprint).ok_or(ApiError{ message: "no active master".into() })?;
+    let master = load_master(&viewer, &fp)?;
+    Ok(entry.security_questions.iter()
+        .map(|q| SecurityAnswer { question: q.question.clone(), answer: gen::security_answer(&master, &entry.postfix, q) })
+        .collect())
+}
 
 #[tauri::command]
 fn delete_entry(id: String) -> bool { store::delete(id) }
@@ -72,14 +268,132 @@ fn reorder_entries(ids: Vec<String>) -> Result<(), ApiError> {
         .map_err(|e| ApiError { message: e.to_string() })
 }
 
+/// Password for a saved entry plus the account identity derived for the same postfix.
+#[derive(Serialize)]
+struct SavedOutput { password: String, username: String, email: Option<String> }
+
+#[tauri::command]
+fn generate_saved(id: String, viewer_password: String) -> Result<SavedOutput, ApiError> {
+    let Some(entry) = store::get(&id) else { return Err(ApiError{ message: "Entry not found".into() }); };
+    let viewer = Zeroizing::new(viewer_password);
+    let prefs = config::read_prefs();
+    let fp = entry.fingerprint.clone().or(prefs.active_fingerprint).ok_or(ApiError{ message: "no active master".into() })?;
+    let master = load_master(&viewer, &fp)?;
+    let password = gen::generate_with(&master, &entry.postfix, &entry.method_id, &entry.gen_options()).map_err(|e| ApiError { message: e.to_string() })?;
+    let email = match prefs.alias_template.as_deref() {
+        Some(t) => Some(gen::email_alias(&master, &entry.postfix, t).map_err(|e| ApiError { message: e.to_string() })?),
+        None => None,
+    };
+    Ok(SavedOutput { password, username: gen::username(&master, &entry.postfix), email })
+}
+
+#[tauri::command]
+fn generate_saved_previous(id: String, viewer_password: String) -> Result<String, ApiError> {
+    let Some(entry) = store::get(&id) else { return Err(ApiError{ message: "Entry not found".into() }); };
+    let counter = entry.previous_counter.ok_or(ApiError{ message: "no previous password".into() })?;
+    let viewer = Zeroizing::new(viewer_password);
+    let fp = entry.fingerprint.clone().or_else(|| config::read_prefs().active_fingerprint).ok_or(ApiError{ message: "no active master".into() })?;
+    let master = load_master(&viewer, &fp)?;
+    let opts = gen::GenOptions { counter, ..entry.gen_options() };
+    gen::generate_with(&master, &entry.postfix, &entry.method_id, &opts).map_err(|e| ApiError { message: e.to_string() })
+}
+
+#[tauri::command]
+fn export_ssh_key(viewer_password: String, postfix: String, comment: Option<String>, passphrase: Option<String>) -> Result<ssh::SshKeyExport, ApiError> {
+    let viewer = Zeroizing::new(viewer_password);
+    let passphrase = passphrase.map(Zeroizing::new);
+    let p = config::read_prefs();
+    let fp = p.active_fingerprint.clone().ok_or(ApiError{ message: "no active master".into() })?;
+    let master = load_master(&viewer, &fp)?;
+    let comment = comment.unwrap_or_else(|| postfix.clone());
+    ssh::export_ed25519(&master, &postfix, 0, &comment, passphrase.as_deref().map(String::as_str)).map_err(|e| ApiError { message: e.to_string() })
+}
+
 #[tauri::command]
-fn generate_saved(id: String, viewer_password: String) -> Result<String, ApiError> {
+fn export_saved_ssh_key(id: String, viewer_password: String, passphrase: Option<String>) -> Result<ssh::SshKeyExport, ApiError> {
     let Some(entry) = store::get(&id) else { return Err(ApiError{ message: "Entry not found".into() }); };
     let viewer = Zeroizing::new(viewer_password);
+    let passphrase = passphrase.map(Zeroizing::new);
     let fp = entry.fingerprint.clone().or_else(|| config::read_prefs().active_fingerprint).ok_or(ApiError{ message: "no active master".into() })?;
-    let master = crypto::load_master(&viewer, &fp).map_err(|e| ApiError { message: e.to_string() })?;
-    let result = gen::generate(&master, &entry.postfix, &entry.method_id);
-    Ok(result)
+    let master = load_master(&viewer, &fp)?;
+    ssh::export_ed25519(&master, &entry.postfix, entry.counter, &entry.label, passphrase.as_deref().map(String::as_str)).map_err(|e| ApiError { message: e.to_string() })
+}
+
+#[tauri::command]
+fn decode_otp_qr(image: Vec<u8>) -> Result<String, ApiError> {
+    otp::decode_qr(&image).map_err(|e| ApiError { message: e.to_string() })
+}
+
+/// Seals the secret from an `otpauth://` URI under the entry's master; `None` removes it.
+#[tauri::command]
+fn set_entry_otp(id: String, viewer_password: String, uri: Option<String>) -> Result<bool, ApiError> {
+    let Some(entry) = store::get(&id) else { return Err(ApiError{ message: "Entry not found".into() }); };
+    let Some(uri) = uri.map(Zeroizing::new) else { return store::set_otp(&id, None).map_err(|e| ApiError { message: e.to_string() }) };
+    let secret = otp::OtpSecret::from_uri(&uri).map_err(|e| ApiError { message: e.to_string() })?;
+    let viewer = Zeroizing::new(viewer_password);
+    let fp = entry.fingerprint.clone().or_else(|| config::read_prefs().active_fingerprint).ok_or(ApiError{ message: "no active master".into() })?;
+    let master = load_master(&viewer, &fp)?;
+    store::set_otp(&id, Some(secret.seal(&master, &entry.id))).map_err(|e| ApiError { message: e.to_string() })
+}
+
+/// Current TOTP code and seconds remaining; for HOTP the next code, advancing the stored counter.
+#[tauri::command]
+fn entry_otp_code(id: String, viewer_password: String) -> Result<otp::OtpCode, ApiError> {
+    let Some(entry) = store::get(&id) else { return Err(ApiError{ message: "Entry not found".into() }); };
+    let sealed = entry.otp.as_ref().ok_or(ApiError{ message: "entry has no otp secret".into() })?;
+    let viewer = Zeroizing::new(viewer_password);
+    let fp = entry.fingerprint.clone().or_else(|| config::read_prefs().active_fingerprint).ok_or(ApiError{ message: "no active master".into() })?;
+    let master = load_master(&viewer, &fp)?;
+    let mut secret = sealed.open(&master, &entry.id).map_err(|e| ApiError { message: e.to_string() })?;
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let code = secret.code(now);
+    if matches!(secret.kind, otp::OtpKind::Hotp { .. }) {
+        // a code whose counter wasn't saved would be handed out again next time
+        secret.advance();
+        let saved = store::set_otp(&id, Some(secret.seal(&master, &entry.id))).map_err(|e| ApiError { message: e.to_string() })?;
+        if !saved { return Err(ApiError{ message: "Entry not found".into() }); }
+    }
+    Ok(code)
+}
+
+#[tauri::command]
+fn set_entry_ssh_agent(id: String, enabled: bool) -> bool { store::set_ssh_agent(&id, enabled) }
+
+/// Derives the keys of the active master's `ssh_agent` entries and serves them on
+/// `paths::ssh_agent_socket()` until `lock_after_seconds` (default 15 min) or `ssh_agent_lock`.
+#[tauri::command]
+fn ssh_agent_unlock(agent: tauri::State<'_, agent::SshAgent>, viewer_password: String, lock_after_seconds: Option<u64>) -> Result<agent::AgentStatus, ApiError> {
+    let viewer = Zeroizing::new(viewer_password);
+    let p = config::read_prefs();
+    let fp = p.active_fingerprint.clone().ok_or(ApiError{ message: "no active master".into() })?;
+    let master = load_master(&viewer, &fp)?;
+    // re-read: unlocking may just have moved the master to its keyed fingerprint
+    let identities = store::list_for_fingerprint(&config::read_prefs().active_fingerprint)
+        .into_iter()
+        .filter(|e| e.ssh_agent)
+        .map(|e| ssh::ed25519_key(&master, &e.postfix, e.counter, &e.label).and_then(agent::Identity::new))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| ApiError { message: e.to_string() })?;
+    agent.listen(&paths::ssh_agent_socket()).map_err(|e| ApiError { message: e.to_string() })?;
+    let lock_after = lock_after_seconds.map(std::time::Duration::from_secs).unwrap_or(agent::DEFAULT_LOCK_AFTER);
+    agent.unlock(identities, lock_after);
+    Ok(agent.status())
+}
+
+#[tauri::command]
+fn ssh_agent_lock(agent: tauri::State<'_, agent::SshAgent>) -> agent::AgentStatus {
+    agent.lock();
+    agent.status()
+}
+
+#[tauri::command]
+fn ssh_agent_status(agent: tauri::State<'_, agent::SshAgent>) -> agent::AgentStatus { agent.status() }
+
+#[tauri::command]
+fn bump_entry_counter(id: String) -> Result<store::Entry, ApiError> {
+    store::bump_counter(&id)
+        .map_err(|e| ApiError { message: e.to_string() })?
+        .ok_or(ApiError{ message: "Entry not found".into() })
 }
 
 #[tauri::command]
@@ -116,22 +430,50 @@ fn is_screen_captured() -> bool {
 
 fn main() {
     tauri::Builder::default()
+        .manage(agent::SshAgent::default())
         .invoke_handler(tauri::generate_handler![
             has_master,
             setup_set_master,
             master_fingerprint,
             reveal_master,
+            change_viewer_password,
+            list_key_slots,
+            add_key_slot,
+            remove_key_slot,
+            add_recovery_code,
+            calibrate_kdf,
+            upgrade_master_kdf,
             list_masters,
             get_active_fingerprint,
             set_active_fingerprint,
             delete_master,
             bind_unbound_entries,
             generate_password,
+            method_info,
+            self_test,
+            entry_method_info,
             list_entries,
             add_entry,
+            suggest_postfix,
+            set_entry_policy,
+            set_entry_lesspass,
+            set_entry_spectre,
+            set_entry_security_questions,
+            entry_security_answers,
             delete_entry,
             reorder_entries,
             generate_saved,
+            generate_saved_previous,
+            bump_entry_counter,
+            export_ssh_key,
+            export_saved_ssh_key,
+            set_entry_ssh_agent,
+            decode_otp_qr,
+            set_entry_otp,
+            entry_otp_code,
+            ssh_agent_unlock,
+            ssh_agent_lock,
+            ssh_agent_status,
             enable_content_protection,
             storage_paths,
             export_entries,
@@ -187,7 +529,7 @@ fn export_entries_csv(path: String) -> Result<(), ApiError> {
 }
 
 #[tauri::command]
-fn import_entries(path: String, passphrase: Option<String>, overwrite: bool) -> Result<usize, ApiError> {
+fn import_entries(path: String, passphrase: Option<String>, overwrite: bool) -> Result<backup::ImportSummary, ApiError> {
     backup::import_from_path(&path, passphrase, overwrite).map_err(|e| ApiError { message: e })
 }
 
@@ -207,7 +549,7 @@ fn import_entries_apply(path: String, passphrase: Option<String>, mapping: Vec<b
 }
 
 #[tauri::command]
-fn import_entries_payload(entries: Vec<store::Entry>, overwrite: bool) -> Result<usize, ApiError> {
+fn import_entries_payload(entries: Vec<store::Entry>, overwrite: bool) -> Result<backup::ImportSummary, ApiError> {
     backup::import_entries_payload(entries, overwrite).map_err(|e| ApiError { message: e })
 }
 
@@ -249,9 +591,13 @@ fn set_prefs(
     copy_on_console_generate: Option<bool>,
     hold_only_reveal: Option<bool>,
     clear_clipboard_on_blur: Option<bool>,
+    alias_template: Option<String>,
 ) -> Result<config::Prefs, ApiError> {
     let mut p = config::read_prefs();
-    if let Some(dm) = default_method { p.default_method = dm; }
+    if let Some(dm) = default_method {
+        dm.parse::<gen::MethodSpec>().map_err(|e| ApiError { message: e.to_string() })?;
+        p.default_method = dm;
+    }
     if let Some(sec) = auto_clear_seconds { p.auto_clear_seconds = sec; }
     if let Some(ms) = mask_sensitive { p.mask_sensitive = ms; }
     if let Some(aq) = autosave_quick { p.autosave_quick = aq; }
@@ -264,6 +610,13 @@ fn set_prefs(
     if let Some(c) = copy_on_console_generate { p.copy_on_console_generate = c; }
     if let Some(h) = hold_only_reveal { p.hold_only_reveal = h; }
     if let Some(cb) = clear_clipboard_on_blur { p.clear_clipboard_on_blur = cb; }
+    // an empty template turns email aliases off
+    if let Some(t) = alias_template {
+        if t.is_empty() { p.alias_template = None } else {
+            gen::check_alias_template(&t).map_err(|e| ApiError { message: e.to_string() })?;
+            p.alias_template = Some(t);
+        }
+    }
     config::write_prefs(&p).map_err(|e| ApiError { message: e.to_string() })?;
     Ok(p)
 }
@@ -0,0 +1,347 @@
+use aes_gcm::{aead::{Aead, KeyInit, Payload}, Aes256Gcm, Key, Nonce};
+use base64::{engine::general_purpose, Engine as _};
+use hmac::{Hmac, Mac};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+use thiserror::Error;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::gen;
+
+const BASE32_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const DEFAULT_DIGITS: u32 = 6;
+const DEFAULT_PERIOD: u64 = 30;
+// 31-bit dynamic truncation never yields more than 10 digits
+const MAX_DIGITS: u32 = 10;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum OtpError {
+    #[error("invalid otpauth uri: {0}")] InvalidUri(String),
+    #[error("invalid image: {0}")] InvalidImage(String),
+    #[error("no QR code found in image")] NoQrCode,
+    #[error("otp secret could not be decrypted")] Decryption,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum OtpAlgorithm { #[default] Sha1, Sha256, Sha512 }
+
+impl OtpAlgorithm {
+    fn mac(self, key: &[u8], msg: &[u8]) -> Vec<u8> {
+        match self {
+            OtpAlgorithm::Sha1 => mac::<Hmac<Sha1>>(key, msg),
+            OtpAlgorithm::Sha256 => mac::<Hmac<Sha256>>(key, msg),
+            OtpAlgorithm::Sha512 => mac::<Hmac<Sha512>>(key, msg),
+        }
+    }
+}
+
+fn mac<M: Mac + KeyInit>(key: &[u8], msg: &[u8]) -> Vec<u8> {
+    let mut m = <M as Mac>::new_from_slice(key).expect("hmac accepts keys of any length");
+    m.update(msg);
+    m.finalize().into_bytes().to_vec()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum OtpKind {
+    Totp { period: u64 },
+    /// `counter` is the value the next code is computed for
+    Hotp { counter: u64 },
+}
+
+/// Decoded OTP parameters. Only ever persisted inside a `SealedOtp`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct OtpSecret {
+    secret: Vec<u8>,
+    pub algorithm: OtpAlgorithm,
+    pub digits: u32,
+    pub kind: OtpKind,
+    #[serde(default)]
+    pub issuer: Option<String>,
+    #[serde(default)]
+    pub account: Option<String>,
+}
+
+impl Drop for OtpSecret {
+    fn drop(&mut self) { self.secret.zeroize(); }
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct OtpCode {
+    pub code: String,
+    /// Seconds until a TOTP code changes; None for HOTP
+    pub seconds_remaining: Option<u64>,
+    pub period: Option<u64>,
+}
+
+impl OtpSecret {
+    /// Parses an `otpauth://totp/…` or `otpauth://hotp/…` URI (Google Authenticator key URI format).
+    pub fn from_uri(uri: &str) -> Result<Self, OtpError> {
+        let invalid = |m: &str| OtpError::InvalidUri(m.to_string());
+        let rest = uri.trim();
+        let rest = rest.get(..10).filter(|s| s.eq_ignore_ascii_case("otpauth://")).map(|_| &rest[10..]).ok_or_else(|| invalid("expected otpauth://"))?;
+        let (kind, rest) = rest.split_once('/').ok_or_else(|| invalid("missing label"))?;
+        let (label, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+        let mut secret = None;
+        let mut issuer = None;
+        let mut algorithm = OtpAlgorithm::default();
+        let mut digits = DEFAULT_DIGITS;
+        let mut period = DEFAULT_PERIOD;
+        let mut counter = None;
+        for pair in query.split('&').filter(|p| !p.is_empty()) {
+            let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+            let v = percent_decode(v, true).ok_or_else(|| invalid("bad percent-encoding"))?;
+            match k.to_ascii_lowercase().as_str() {
+                "secret" => secret = Some(base32_decode(&v).ok_or_else(|| invalid("secret is not base32"))?),
+                "issuer" => issuer = Some(v),
+                "algorithm" => algorithm = match v.to_ascii_uppercase().as_str() {
+                    "SHA1" => OtpAlgorithm::Sha1,
+                    "SHA256" => OtpAlgorithm::Sha256,
+                    "SHA512" => OtpAlgorithm::Sha512,
+                    _ => return Err(invalid("unsupported algorithm")),
+                },
+                "digits" => digits = v.parse().ok().filter(|d| (DEFAULT_DIGITS..=MAX_DIGITS).contains(d)).ok_or_else(|| invalid("digits must be 6..=10"))?,
+                "period" => period = v.parse().ok().filter(|p| *p > 0).ok_or_else(|| invalid("period must be a positive number of seconds"))?,
+                "counter" => counter = Some(v.parse().map_err(|_| invalid("counter must be a number"))?),
+                // image, color and other vendor extensions
+                _ => {}
+            }
+        }
+        let secret = secret.filter(|s| !s.is_empty()).ok_or_else(|| invalid("missing secret"))?;
+        let kind = match kind.to_ascii_lowercase().as_str() {
+            "totp" => OtpKind::Totp { period },
+            "hotp" => OtpKind::Hotp { counter: counter.ok_or_else(|| invalid("hotp needs a counter"))? },
+            _ => return Err(invalid("type must be totp or hotp")),
+        };
+
+        // label is "issuer:account" or just "account"; an issuer parameter wins
+        let label = percent_decode(label, false).ok_or_else(|| invalid("bad percent-encoding"))?;
+        let (prefix, account) = match label.split_once(':') {
+            Some((i, a)) => (Some(i.trim().to_string()), a.trim().to_string()),
+            None => (None, label.trim().to_string()),
+        };
+        Ok(OtpSecret {
+            secret,
+            algorithm,
+            digits,
+            kind,
+            issuer: issuer.or(prefix).filter(|i| !i.is_empty()),
+            account: Some(account).filter(|a| !a.is_empty()),
+        })
+    }
+
+    /// RFC 4226 HOTP value for `counter`
+    fn hotp(&self, counter: u64) -> String {
+        let digest = self.algorithm.mac(&self.secret, &counter.to_be_bytes());
+        let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+        let bin = u32::from_be_bytes([digest[offset], digest[offset + 1], digest[offset + 2], digest[offset + 3]]) & 0x7fff_ffff;
+        format!("{:0width$}", u64::from(bin) % 10u64.pow(self.digits), width = self.digits as usize)
+    }
+
+    /// Current code: RFC 6238 for TOTP at `unix_time`, the stored counter for HOTP
+    /// (the caller advances and re-seals it afterwards).
+    pub fn code(&self, unix_time: u64) -> OtpCode {
+        match self.kind {
+            OtpKind::Totp { period } => OtpCode {
+                code: self.hotp(unix_time / period),
+                seconds_remaining: Some(period - unix_time % period),
+                period: Some(period),
+            },
+            OtpKind::Hotp { counter } => OtpCode { code: self.hotp(counter), seconds_remaining: None, period: None },
+        }
+    }
+
+    pub fn advance(&mut self) {
+        if let OtpKind::Hotp { counter } = &mut self.kind { *counter = counter.wrapping_add(1); }
+    }
+
+    /// Encrypts under `gen::otp_key(master)` with AES-256-GCM, binding the ciphertext to `entry_id`.
+    pub fn seal(&self, master: &str, entry_id: &str) -> SealedOtp {
+        let key = Zeroizing::new(gen::otp_key(master));
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&*key));
+        let mut nonce = [0u8; 12];
+        OsRng.fill_bytes(&mut nonce);
+        let plain = Zeroizing::new(serde_json::to_vec(self).expect("otp secret serializes"));
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: &plain, aad: entry_id.as_bytes() })
+            .expect("aes-gcm encryption cannot fail for in-memory buffers");
+        SealedOtp {
+            nonce_b64: general_purpose::STANDARD_NO_PAD.encode(nonce),
+            ciphertext_b64: general_purpose::STANDARD_NO_PAD.encode(ciphertext),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SealedOtp {
+    pub nonce_b64: String,
+    pub ciphertext_b64: String,
+}
+
+impl SealedOtp {
+    pub fn open(&self, master: &str, entry_id: &str) -> Result<OtpSecret, OtpError> {
+        let nonce = general_purpose::STANDARD_NO_PAD.decode(&self.nonce_b64).map_err(|_| OtpError::Decryption)?;
+        let ciphertext = general_purpose::STANDARD_NO_PAD.decode(&self.ciphertext_b64).map_err(|_| OtpError::Decryption)?;
+        if nonce.len() != 12 { return Err(OtpError::Decryption); }
+        let key = Zeroizing::new(gen::otp_key(master));
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&*key));
+        let plain = Zeroizing::new(cipher
+            .decrypt(Nonce::from_slice(&nonce), Payload { msg: &ciphertext, aad: entry_id.as_bytes() })
+            .map_err(|_| OtpError::Decryption)?);
+        serde_json::from_slice(&plain).map_err(|_| OtpError::Decryption)
+    }
+}
+
+/// Text of the first readable QR code in an image (PNG, JPEG, …), normally an `otpauth://` URI.
+pub fn decode_qr(image_bytes: &[u8]) -> Result<String, OtpError> {
+    let img = image::load_from_memory(image_bytes).map_err(|e| OtpError::InvalidImage(e.to_string()))?.to_luma8();
+    let mut prepared = rqrr::PreparedImage::prepare_from_greyscale(img.width() as usize, img.height() as usize, |x, y| {
+        img.get_pixel(x as u32, y as u32)[0]
+    });
+    prepared
+        .detect_grids()
+        .into_iter()
+        .find_map(|grid| grid.decode().ok().map(|(_, text)| text))
+        .ok_or(OtpError::NoQrCode)
+}
+
+// RFC 4648 base32; case, spaces and padding are ignored as authenticator apps do
+fn base32_decode(s: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut buf = 0u32;
+    let mut bits = 0;
+    for c in s.bytes().filter(|c| !matches!(c, b' ' | b'-' | b'=')) {
+        let v = BASE32_ALPHABET.iter().position(|&a| a == c.to_ascii_uppercase())? as u32;
+        buf = (buf << 5) | v;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buf >> bits) as u8);
+            buf &= (1 << bits) - 1;
+        }
+    }
+    Some(out)
+}
+
+fn percent_decode(s: &str, plus_is_space: bool) -> Option<String> {
+    let mut out = Vec::with_capacity(s.len());
+    let mut bytes = s.bytes();
+    while let Some(b) = bytes.next() {
+        match b {
+            b'%' => {
+                let hex = [bytes.next()?, bytes.next()?];
+                out.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+            }
+            b'+' if plus_is_space => out.push(b' '),
+            _ => out.push(b),
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secret(key: &[u8], algorithm: OtpAlgorithm, digits: u32, kind: OtpKind) -> OtpSecret {
+        OtpSecret { secret: key.to_vec(), algorithm, digits, kind, issuer: None, account: None }
+    }
+
+    #[test]
+    fn rfc4226_hotp_vectors() {
+        let expected = ["755224", "287082", "359152", "969429", "338314", "254676", "287922", "162583", "399871", "520489"];
+        let mut s = secret(b"12345678901234567890", OtpAlgorithm::Sha1, 6, OtpKind::Hotp { counter: 0 });
+        for want in expected {
+            assert_eq!(s.code(0), OtpCode { code: want.into(), seconds_remaining: None, period: None });
+            s.advance();
+        }
+    }
+
+    #[test]
+    fn rfc6238_totp_vectors() {
+        let keys = [
+            (OtpAlgorithm::Sha1, &b"12345678901234567890"[..]),
+            (OtpAlgorithm::Sha256, &b"12345678901234567890123456789012"[..]),
+            (OtpAlgorithm::Sha512, &b"1234567890123456789012345678901234567890123456789012345678901234"[..]),
+        ];
+        let vectors: [(u64, [&str; 3]); 6] = [
+            (59, ["94287082", "46119246", "90693936"]),
+            (1111111109, ["07081804", "68084774", "25091201"]),
+            (1111111111, ["14050471", "67062674", "99943326"]),
+            (1234567890, ["89005924", "91819424", "93441116"]),
+            (2000000000, ["69279037", "90698825", "38618901"]),
+            (20000000000, ["65353130", "77737706", "47863826"]),
+        ];
+        for (time, codes) in vectors {
+            for ((alg, key), want) in keys.iter().zip(codes) {
+                let s = secret(key, *alg, 8, OtpKind::Totp { period: 30 });
+                assert_eq!(s.code(time).code, want, "{alg:?} at {time}");
+            }
+        }
+        let s = secret(keys[0].1, OtpAlgorithm::Sha1, 6, OtpKind::Totp { period: 30 });
+        assert_eq!(s.code(59), OtpCode { code: "287082".into(), seconds_remaining: Some(1), period: Some(30) });
+        assert_eq!(s.code(60).seconds_remaining, Some(30));
+    }
+
+    #[test]
+    fn parses_otpauth_uris() {
+        // "12345678901234567890" in base32
+        let s = OtpSecret::from_uri("otpauth://totp/ACME%20Co:john.doe@email.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&issuer=ACME+Co&algorithm=SHA1&digits=8&period=30").unwrap();
+        assert_eq!(s.secret, b"12345678901234567890");
+        assert_eq!((s.algorithm, s.digits, &s.kind), (OtpAlgorithm::Sha1, 8, &OtpKind::Totp { period: 30 }));
+        assert_eq!(s.issuer.as_deref(), Some("ACME Co"));
+        assert_eq!(s.account.as_deref(), Some("john.doe@email.com"));
+        assert_eq!(s.code(59).code, "94287082");
+
+        // defaults, lowercase/unpadded secret, issuer only in the label
+        let s = OtpSecret::from_uri("OTPAUTH://TOTP/Example:alice?secret=gezdgnbvgy3tqojq").unwrap();
+        assert_eq!((s.algorithm, s.digits, &s.kind), (OtpAlgorithm::Sha1, 6, &OtpKind::Totp { period: 30 }));
+        assert_eq!((s.issuer.as_deref(), s.account.as_deref()), (Some("Example"), Some("alice")));
+        assert_eq!(s.secret, b"1234567890");
+
+        let s = OtpSecret::from_uri("otpauth://hotp/bob?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&counter=3&algorithm=sha256").unwrap();
+        assert_eq!((s.algorithm, &s.kind, s.issuer.as_deref()), (OtpAlgorithm::Sha256, &OtpKind::Hotp { counter: 3 }, None));
+
+        for bad in [
+            "https://totp/x?secret=GEZDGNBV",
+            "otpauth://totp/x",
+            "otpauth://totp/x?secret=not*base32",
+            "otpauth://totp/x?secret=GEZDGNBV&digits=5",
+            "otpauth://totp/x?secret=GEZDGNBV&period=0",
+            "otpauth://totp/x?secret=GEZDGNBV&algorithm=MD5",
+            "otpauth://hotp/x?secret=GEZDGNBV",
+            "otpauth://motp/x?secret=GEZDGNBV",
+        ] {
+            assert!(matches!(OtpSecret::from_uri(bad), Err(OtpError::InvalidUri(_))), "{bad}");
+        }
+    }
+
+    #[test]
+    fn decodes_otpauth_qr_codes() {
+        // otpauth_qr_test.png: the URI below as a version 6, level M QR code
+        let uri = decode_qr(include_bytes!("otpauth_qr_test.png")).unwrap();
+        assert_eq!(uri, "otpauth://totp/Example:alice@example.com?secret=JBSWY3DPEHPK3PXP&issuer=Example");
+        let s = OtpSecret::from_uri(&uri).unwrap();
+        assert_eq!((s.issuer.as_deref(), s.account.as_deref()), (Some("Example"), Some("alice@example.com")));
+        assert_eq!(s.secret, b"Hello!\xde\xad\xbe\xef");
+
+        assert_eq!(decode_qr(include_bytes!("../icons/icon.png")), Err(OtpError::NoQrCode));
+        assert!(matches!(decode_qr(b"not an image"), Err(OtpError::InvalidImage(_))));
+    }
+
+    #[test]
+    fn sealed_secret_needs_master_and_entry() {
+        let s = OtpSecret::from_uri("otpauth://totp/x?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ").unwrap();
+        let sealed = s.seal("master", "entry-1");
+        assert!(!sealed.ciphertext_b64.contains("GEZDGNBV"));
+        assert_eq!(sealed.open("master", "entry-1").unwrap(), s);
+        assert_eq!(sealed.open("other", "entry-1"), Err(OtpError::Decryption));
+        assert_eq!(sealed.open("master", "entry-2"), Err(OtpError::Decryption));
+        // fresh nonce every time
+        assert_ne!(s.seal("master", "entry-1").ciphertext_b64, sealed.ciphertext_b64);
+    }
+}
@@ -1,5 +1,5 @@
 use directories::ProjectDirs;
-use std::{fs, path::PathBuf};
+use std::{fs, io::Write, path::{Path, PathBuf}};
 
 pub fn app_data_dir() -> PathBuf {
     if let Ok(custom) = std::env::var("SAFORIA_DATA_DIR") {
@@ -23,6 +23,35 @@ pub fn entries_file() -> PathBuf {
     dir
 }
 
+/// Socket of the built-in ssh-agent, alone in a directory the agent keeps at mode 0700
+pub fn ssh_agent_socket() -> PathBuf {
+    let mut dir = app_data_dir();
+    let _ = ensure_dir(&dir);
+    dir.push("agent");
+    dir.push("agent.sock");
+    dir
+}
+
+/// Random per-install salt for master fingerprints (see crypto::fingerprint)
+pub fn install_salt_file() -> PathBuf {
+    let mut dir = app_data_dir();
+    let _ = ensure_dir(&dir);
+    dir.push("install.salt");
+    dir
+}
+
+/// Replaces `path` through a synced temporary file and a rename, so readers see either the
+/// old or the new contents and a crash never leaves a truncated file.
+pub fn write_atomic(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    let mut f = fs::File::create(&tmp)?;
+    f.write_all(data)?;
+    f.sync_all()?;
+    fs::rename(&tmp, path)
+}
+
 pub fn masters_dir() -> PathBuf {
     let mut dir = app_data_dir();
     let _ = ensure_dir(&dir);
@@ -31,6 +60,10 @@ pub fn masters_dir() -> PathBuf {
     dir
 }
 
+/// Held by tests that point `SAFORIA_DATA_DIR` elsewhere, as the variable is process-wide
+#[cfg(test)]
+pub static TEST_DATA_DIR: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -38,6 +71,7 @@ mod tests {
 
     #[test]
     fn uses_custom_env_dir_when_set() {
+        let _lock = TEST_DATA_DIR.lock().unwrap_or_else(|e| e.into_inner());
         let tmp = tempfile::tempdir().unwrap();
         env::set_var("SAFORIA_DATA_DIR", tmp.path());
         let p = app_data_dir();
@@ -2,7 +2,9 @@ use serde::{Deserialize, Serialize};
 use std::{fs, time::{SystemTime, UNIX_EPOCH}};
 use rand::{RngCore, rngs::OsRng};
 
-use crate::paths::entries_file;
+use crate::gen::{self, GenError, GenOptions, LessPassProfile, Policy, SecurityQuestion, SpectreProfile};
+use crate::otp::SealedOtp;
+use crate::paths::{entries_file, write_atomic};
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Entry {
@@ -15,6 +17,37 @@ pub struct Entry {
     pub order: i64,
     #[serde(default)]
     pub fingerprint: Option<String>,
+    #[serde(default)]
+    pub policy: Option<Policy>,
+    #[serde(default)]
+    pub counter: u32,
+    /// Counter before the last bump, so the old password stays derivable for change-password forms
+    #[serde(default)]
+    pub previous_counter: Option<u32>,
+    #[serde(default)]
+    pub lesspass: Option<LessPassProfile>,
+    #[serde(default)]
+    pub spectre: Option<SpectreProfile>,
+    /// Serve this entry's derived Ed25519 key from the built-in ssh-agent
+    #[serde(default)]
+    pub ssh_agent: bool,
+    /// TOTP/HOTP secret sealed under the entry's master (see `otp::OtpSecret::seal`)
+    #[serde(default)]
+    pub otp: Option<SealedOtp>,
+    /// Security questions whose answers are derived on demand (see `gen::security_answer`)
+    #[serde(default)]
+    pub security_questions: Vec<SecurityQuestion>,
+}
+
+impl Entry {
+    pub fn gen_options(&self) -> GenOptions<'_> {
+        GenOptions {
+            policy: self.policy.as_ref(),
+            counter: self.counter,
+            lesspass: self.lesspass.as_ref(),
+            spectre: self.spectre.as_ref(),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -31,7 +64,7 @@ pub fn dump_all() -> EntriesFile { read_all() }
 
 pub fn write_all(all: &EntriesFile) -> Result<(), std::io::Error> {
     let path = entries_file();
-    fs::write(path, serde_json::to_string_pretty(all).unwrap())
+    write_atomic(&path, serde_json::to_string_pretty(all).unwrap().as_bytes())
 }
 
 fn new_id() -> String {
@@ -69,8 +102,7 @@ pub fn list_for_fingerprint(active: &Option<String>) -> Vec<Entry> {
     v
 }
 
-pub fn add(label: String, postfix: String, method_id: String, active: &Option<String>) -> Entry {
-    let mut all = read_all();
+pub fn add(label: String, postfix: String, method_id: String, policy: Option<Policy>, lesspass: Option<LessPassProfile>, spectre: Option<SpectreProfile>, active: &Option<String>) -> Result<Entry, GenError> {
     let entry = Entry {
         id: new_id(),
         label,
@@ -79,14 +111,24 @@ pub fn add(label: String, postfix: String, method_id: String, active: &Option<St
         created_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs(),
         order: 0,
         fingerprint: None,
+        policy,
+        counter: 0,
+        previous_counter: None,
+        lesspass,
+        spectre,
+        ssh_agent: false,
+        otp: None,
+        security_questions: Vec::new(),
     };
+    gen::check(&entry.method_id, &entry.gen_options())?;
+    let mut all = read_all();
     let mut entry = entry;
     if let Some(fp) = active {
         entry.fingerprint = Some(fp.clone());
     }
     all.entries.insert(0, entry.clone());
     let _ = write_all(&all);
-    entry
+    Ok(entry)
 }
 
 pub fn delete(id: String) -> bool {
@@ -97,6 +139,70 @@ pub fn delete(id: String) -> bool {
     all.entries.len() < before
 }
 
+pub fn set_policy(id: &str, policy: Option<Policy>) -> Result<bool, GenError> {
+    let mut all = read_all();
+    let Some(entry) = all.entries.iter_mut().find(|e| e.id == id) else { return Ok(false) };
+    gen::check(&entry.method_id, &GenOptions { policy: policy.as_ref(), ..entry.gen_options() })?;
+    entry.policy = policy;
+    let _ = write_all(&all);
+    Ok(true)
+}
+
+pub fn set_lesspass(id: &str, profile: Option<LessPassProfile>) -> Result<bool, GenError> {
+    let mut all = read_all();
+    let Some(entry) = all.entries.iter_mut().find(|e| e.id == id) else { return Ok(false) };
+    gen::check(&entry.method_id, &GenOptions { lesspass: profile.as_ref(), ..entry.gen_options() })?;
+    entry.lesspass = profile;
+    let _ = write_all(&all);
+    Ok(true)
+}
+
+pub fn set_ssh_agent(id: &str, enabled: bool) -> bool {
+    let mut all = read_all();
+    let Some(entry) = all.entries.iter_mut().find(|e| e.id == id) else { return false };
+    entry.ssh_agent = enabled;
+    let _ = write_all(&all);
+    true
+}
+
+pub fn set_otp(id: &str, otp: Option<SealedOtp>) -> Result<bool, std::io::Error> {
+    let mut all = read_all();
+    let Some(entry) = all.entries.iter_mut().find(|e| e.id == id) else { return Ok(false) };
+    entry.otp = otp;
+    write_all(&all)?;
+    Ok(true)
+}
+
+pub fn set_security_questions(id: &str, questions: Vec<SecurityQuestion>) -> Result<bool, GenError> {
+    gen::check_security_questions(&questions)?;
+    let mut all = read_all();
+    let Some(entry) = all.entries.iter_mut().find(|e| e.id == id) else { return Ok(false) };
+    entry.security_questions = questions;
+    let _ = write_all(&all);
+    Ok(true)
+}
+
+pub fn set_spectre(id: &str, profile: Option<SpectreProfile>) -> Result<bool, GenError> {
+    let mut all = read_all();
+    let Some(entry) = all.entries.iter_mut().find(|e| e.id == id) else { return Ok(false) };
+    gen::check(&entry.method_id, &GenOptions { spectre: profile.as_ref(), ..entry.gen_options() })?;
+    entry.spectre = profile;
+    let _ = write_all(&all);
+    Ok(true)
+}
+
+pub fn bump_counter(id: &str) -> Result<Option<Entry>, GenError> {
+    let mut all = read_all();
+    let Some(entry) = all.entries.iter_mut().find(|e| e.id == id) else { return Ok(None) };
+    let next = entry.counter.checked_add(1).ok_or_else(|| GenError::InvalidParams("rotation counter overflow".into()))?;
+    gen::check(&entry.method_id, &GenOptions { counter: next, ..entry.gen_options() })?;
+    entry.previous_counter = Some(entry.counter);
+    entry.counter = next;
+    let bumped = entry.clone();
+    let _ = write_all(&all);
+    Ok(Some(bumped))
+}
+
 pub fn get(id: &str) -> Option<Entry> {
     read_all().entries.into_iter().find(|e| e.id == id)
 }
@@ -174,3 +280,17 @@ pub fn bind_unbound_to(active: &str) -> usize {
     let _ = write_all(&all);
     count
 }
+
+/// Moves every entry bound to `from` over to `to`; used when a master's fingerprint changes.
+pub fn rebind_fingerprint(from: &str, to: &str) -> Result<usize, std::io::Error> {
+    let mut all = read_all();
+    let mut count = 0usize;
+    for e in all.entries.iter_mut() {
+        if e.fingerprint.as_deref() == Some(from) {
+            e.fingerprint = Some(to.to_string());
+            count += 1;
+        }
+    }
+    if count > 0 { write_all(&all)?; }
+    Ok(count)
+}
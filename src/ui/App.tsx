@@ -1,8 +1,10 @@
 import React, { useEffect, useMemo, useState } from 'react'
 import { invoke, listen } from '../bridge'
+import { saveNewMaster } from './setupMaster'
 import { ToastContainer, useToasts } from './Toast'
 import { PasswordInput } from './PasswordInput'
 import { SetupScreen, type SetupState } from './screens/SetupScreen'
+import { RecoveryCode } from './components/RecoveryCode'
 import { Unified } from './screens/Unified'
 import { MobileUnified } from './screens/MobileUnified'
 import { MobileRoot } from './mobile/MobileRoot'
@@ -17,15 +19,33 @@ import { ClipboardProgress } from './ClipboardProgress'
 
 const STRONG_DEFAULT = 'len36_strong'
 
-const methods = [
-  { id: 'legacy_v1', name: 'Legacy v1 (MD5 + Base64, trim =)', legacy: true },
-  { id: 'legacy_v2', name: 'Legacy v2 (SHA256 + URL-Base64 .-_)', legacy: true },
+type MethodInfo = { id: string, alphabet: string, alphabet_size: number, length: number | null, entropy_bits: number, primitive: string, deprecated: boolean, high_value: boolean, supports_counter: boolean }
+
+// Display names only; strength, deprecation and high-value flags come from the backend (method_info)
+const baseMethods = [
+  { id: 'legacy_v1', name: 'Legacy v1 (MD5 + Base64, trim =)' },
+  { id: 'legacy_v2', name: 'Legacy v2 (SHA256 + URL-Base64 .-_)' },
   { id: 'len10_alnum', name: '10 chars (A-Za-z0-9)' },
   { id: 'len20_alnum', name: '20 chars (A-Za-z0-9)' },
   { id: 'len36_alnum', name: '36 chars (A-Za-z0-9)' },
   { id: 'len10_strong', name: '10 chars + symbols' },
   { id: 'len20_strong', name: '20 chars + symbols' },
   { id: 'len36_strong', name: '36 chars + symbols (default)' },
+  { id: 'v3_len20_alnum', name: 'v3 · 20 chars (HMAC, framed inputs)' },
+  { id: 'v3_len20_strong', name: 'v3 · 20 chars + symbols (HMAC, framed inputs)' },
+  { id: 'v3_len36_strong', name: 'v3 · 36 chars + symbols (HMAC, framed inputs)' },
+  { id: 'argon_len24_alnum', name: 'Argon2id · 24 chars (slow: ~19 MiB, 2 passes per generate)' },
+  { id: 'argon_len24_strong', name: 'Argon2id · 24 chars + symbols (slow: ~19 MiB, 2 passes per generate)' },
+  { id: 'pin_len4_safe', name: 'PIN · 4 digits (skips 1111/1234/years)' },
+  { id: 'pin_len6_safe', name: 'PIN · 6 digits (skips 111111/123456)' },
+  { id: 'pin_len8', name: 'Digits only · 8' },
+  { id: 'sgp_md5_len10', name: 'SuperGenPass (MD5, 10 chars; postfix = site URL or domain)' },
+  { id: 'pwdhash', name: 'PwdHash (Stanford; postfix = site URL or domain)' },
+  { id: 'lesspass', name: 'LessPass v2 compatible (default profile: 16 chars, all rules, counter 1)' },
+  { id: 'bip39_words12', name: 'BIP39 · 12-word seed phrase' },
+  { id: 'bip39_words24', name: 'BIP39 · 24-word seed phrase' },
+  { id: 'words6_dash', name: 'Passphrase · 6 EFF words (about 77 bits)' },
+  { id: 'words6_space_cap', name: 'Passphrase · 6 Capitalized EFF Words' },
 ]
 
 export function App() {
@@ -42,6 +62,7 @@ export function App() {
   const [busy, setBusy] = useState(false)
   const [setupErr, setSetupErr] = useState('')
   const [setupMaster, setSetupMaster] = useState<SetupState>({ master: '', master2: '', viewer: '', viewer2: '' })
+  const [recoveryCode, setRecoveryCode] = useState('')
   const [captured, setCaptured] = useState(false)
   const [maskSensitive, setMaskSensitive] = useState(false)
   const [blockWhileCaptured, setBlockWhileCaptured] = useState(true)
@@ -56,6 +77,14 @@ export function App() {
 
   const { toasts, push, remove } = useToasts()
   const { t } = useI18n()
+  const [methodInfo, setMethodInfo] = useState<Record<string, MethodInfo>>({})
+  const methods = useMemo(() => baseMethods.map(m => {
+    const info = methodInfo[m.id]
+    if (!info) return m
+    const bits = `${Math.floor(info.entropy_bits)} ${t('methodBits')}`
+    const name = `${m.name} · ${bits}${info.deprecated ? ` · ${t('methodDeprecated')}` : ''}`
+    return { ...m, name, highValue: info.high_value, deprecated: info.deprecated }
+  }), [methodInfo, t])
   const testMode = useMemo(() => {
     try { return !!(globalThis as any).SAFORIA_MOCK || new URLSearchParams(globalThis.location?.search || '').get('test') === '1' } catch { return false }
   }, [])
@@ -66,6 +95,12 @@ export function App() {
 
   useEffect(() => {
     refresh()
+    invoke<{ version: number, vectors: number, failures: string[] }>('self_test').then(r => {
+      if (r.failures.length) push(t('selfTestFailed'), 'error')
+    }).catch(() => {})
+    invoke<MethodInfo[]>('method_info', { methodIds: baseMethods.map(m => m.id) })
+      .then(list => setMethodInfo(Object.fromEntries(list.map(i => [i.id, i]))))
+      .catch(() => {})
     // load preferences
     invoke<{ default_method: string, auto_clear_seconds: number, mask_sensitive: boolean, autosave_quick?: boolean, block_while_captured?: boolean, show_postfix_in_list?: boolean, viewer_prompt_timeout_seconds?: number, output_clear_seconds?: number, output_extend_seconds?: number, copy_on_console_generate?: boolean, hold_only_reveal?: boolean, clear_clipboard_on_blur?: boolean }>('get_prefs').then(p => {
       if (p?.default_method) setDefaultMethod(p.default_method)
@@ -143,7 +178,12 @@ export function App() {
     if (setupMaster.viewer !== setupMaster.viewer2) { setSetupErr(t('viewerMismatch') || 'Viewer passwords do not match'); return }
     setBusy(true)
     try {
-      await invoke('setup_set_master', { viewerPassword: setupMaster.viewer, masterPassword: setupMaster.master })
+      const args = { viewerPassword: setupMaster.viewer, masterPassword: setupMaster.master }
+      const fp = await saveNewMaster(args, () => confirm(t('confirmNormalizeMaster')))
+      if (setupMaster.recovery) {
+        try { setRecoveryCode(await invoke<string>('add_recovery_code', { viewerPassword: setupMaster.viewer, fingerprint: fp })) }
+        catch (err: any) { push(t('failedPrefix') + String(err), 'error') }
+      }
       setSetupMaster({ master: '', master2: '', viewer: '', viewer2: '' })
       await refresh()
       push(t('toastMasterSaved'), 'success')
@@ -161,6 +201,17 @@ export function App() {
   return (
     <div className="container">
       <ToastContainer toasts={toasts} onClose={remove} />
+      {recoveryCode && (
+        <div className="modal-backdrop">
+          <div className="modal" role="dialog" aria-modal="true" aria-labelledby="recovery-code-title">
+            <h3 id="recovery-code-title" className="card-title">{t('recoveryCode')}</h3>
+            <RecoveryCode code={recoveryCode} />
+            <div className="row" style={{ marginTop: 8 }}>
+              <button className="btn primary" autoFocus onClick={() => setRecoveryCode('')}>{t('recoveryCodeSaved')}</button>
+            </div>
+          </div>
+        </div>
+      )}
       <ClipboardProgress />
       {hasMaster && (
         <div className="row app-header app-header-left" style={{ alignItems: 'center', justifyContent: 'space-between', flexWrap: 'nowrap' }}>
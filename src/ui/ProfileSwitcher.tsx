@@ -1,11 +1,13 @@
 import React from 'react'
 import { invoke } from '../bridge'
+import { saveNewMaster } from './setupMaster'
 import { Preferences } from './screens/Preferences'
 import { Backup } from './screens/Backup'
 import { on, emit } from './events'
 import { useI18n } from './i18n'
 import { HowItWorks } from './components/HowItWorks'
 import { useFocusTrap } from './a11y'
+import { RecoveryCode } from './components/RecoveryCode'
 
 function shortFp(fp: string) {
   if (fp.length <= 12) return fp
@@ -52,6 +54,8 @@ export function ProfileSwitcher({ onToast, methods, defaultMethod, autoClearSeco
   const [revealBusy, setRevealBusy] = React.useState(false)
   const [revealViewer, setRevealViewer] = React.useState('')
   const [revealed, setRevealed] = React.useState('')
+  const [rekeyTarget, setRekeyTarget] = React.useState<string | null>(null)
+  const [slotsTarget, setSlotsTarget] = React.useState<string | null>(null)
   const { t } = useI18n()
 
   async function refresh() {
@@ -102,9 +106,11 @@ export function ProfileSwitcher({ onToast, methods, defaultMethod, autoClearSeco
           </div>
           {list.length === 0 && (<div style={{ padding: 12 }} className="muted">{t('noneSaved')}</div>)}
           {list.map(fp => (
-            <div key={fp} style={{ display: 'grid', gridTemplateColumns: '1fr auto auto auto', gap: 8, alignItems: 'center', padding: 10, background: active===fp ? 'rgba(59,130,246,0.1)' : undefined }}>
+            <div key={fp} style={{ display: 'grid', gridTemplateColumns: '1fr auto auto auto auto auto', gap: 8, alignItems: 'center', padding: 10, background: active===fp ? 'rgba(59,130,246,0.1)' : undefined }}>
               <div className="password" title={fp}>{shortFp(fp)} {active===fp && <span className="badge" title={t('active')}>{t('active')}</span>}</div>
               <button className="btn small" aria-label={t('viewMaster')} title={t('viewMaster')} onClick={() => { setRevealTarget(fp); setRevealed(''); setRevealViewer(''); setRevealBusy(false) }}>{'👁'}</button>
+              <button className="btn small" aria-label={t('changeViewer')} title={t('changeViewer')} onClick={() => setRekeyTarget(fp)}>{'🔑'}</button>
+              <button className="btn small" aria-label={t('keySlots')} title={t('keySlots')} onClick={() => setSlotsTarget(fp)}>{'🗝'}</button>
               <button className="btn small" disabled={active === fp} aria-label={t('use')} title={t('use')} onClick={async () => { try { await invoke('set_active_fingerprint', { fp }); setActive(fp); onToast(t('toastActiveChanged'), 'success'); setOpen(false) } catch (e: any) { onToast(String(e), 'error') } }}>{t('use')}</button>
               <button className="btn small danger" aria-label={t('deleteMaster')} title={t('deleteMaster')} onClick={async () => { if (!confirm(t('confirmDeleteMaster'))) return; try { const ok = await invoke<boolean>('delete_master', { fp }); if (ok) { onToast(t('toastMasterDeleted'), 'success'); refresh() } else { onToast(t('toastMasterDeleteFailed'), 'error') } } catch (e:any) { onToast(String(e), 'error') } }}>{t('del')}</button>
             </div>
@@ -125,6 +131,8 @@ export function ProfileSwitcher({ onToast, methods, defaultMethod, autoClearSeco
                   <div className="password" title={fp} style={{ fontSize: 16 }}>{shortFp(fp)} {active===fp && <span className="badge" title={t('active')}>{t('active')}</span>}</div>
                   <div className="row" style={{ gap: 8 }}>
                     <button className="btn" onClick={() => { setRevealTarget(fp); setRevealed(''); setRevealViewer(''); setRevealBusy(false) }}>{t('viewMaster')}</button>
+                    <button className="btn" onClick={() => setRekeyTarget(fp)}>{t('changeViewer')}</button>
+                    <button className="btn" onClick={() => setSlotsTarget(fp)}>{t('keySlots')}</button>
                     <button className="btn" disabled={active === fp} onClick={async () => { try { await invoke('set_active_fingerprint', { fp }); setActive(fp); onToast(t('toastActiveChanged'), 'success'); setOpen(false) } catch (e:any) { onToast(String(e), 'error') } }}>{t('use')}</button>
                     <button className="btn danger" onClick={async () => { if (!confirm(t('confirmDeleteMaster'))) return; try { const ok = await invoke<boolean>('delete_master', { fp }); if (ok) { onToast(t('toastMasterDeleted'), 'success'); refresh() } else { onToast(t('toastMasterDeleteFailed'), 'error') } } catch (e:any) { onToast(String(e), 'error') } }}>{t('del')}</button>
                   </div>
@@ -166,6 +174,18 @@ export function ProfileSwitcher({ onToast, methods, defaultMethod, autoClearSeco
         </div>
       )}
 
+      {slotsTarget && (
+        <div className="modal-backdrop" onClick={() => setSlotsTarget(null)}>
+          <KeySlotsModal fp={slotsTarget} onClose={() => setSlotsTarget(null)} onToast={onToast} />
+        </div>
+      )}
+
+      {rekeyTarget && (
+        <div className="modal-backdrop" onClick={() => setRekeyTarget(null)}>
+          <ChangeViewerModal fp={rekeyTarget} onClose={() => setRekeyTarget(null)} onToast={onToast} />
+        </div>
+      )}
+
       {revealTarget && (
         <div className="modal-backdrop" onClick={() => { setRevealTarget(null); setRevealed(''); setRevealViewer(''); }}>
           <div className="modal" role="dialog" aria-modal="true" aria-labelledby="reveal-master-title" onClick={e => e.stopPropagation()} style={{ maxWidth: 520 }}>
@@ -232,7 +252,7 @@ function AddMasterModal({ busy, setBusy, onClose, onToast, m1, m2, v1, v2, setM1
         <button className="btn primary" disabled={busy || !m1 || m1!==m2 || !v1 || v1!==v2} onClick={async () => {
           setBusy(true)
           try {
-            const fp = await invoke<string>('setup_set_master', { viewerPassword: v1, masterPassword: m1 })
+            const fp = await saveNewMaster({ viewerPassword: v1, masterPassword: m1 }, () => confirm(t('confirmNormalizeMaster')))
             onToast(t('toastMasterSaved'), 'success'); onClose(); setM1(''); setM2(''); setV1(''); setV2(''); onCreated(fp)
           } catch (e:any) { onToast(t('failedPrefix') + String(e), 'error') }
           finally { setBusy(false) }
@@ -243,6 +263,109 @@ function AddMasterModal({ busy, setBusy, onClose, onToast, m1, m2, v1, v2, setM1
   )
 }
 
+type SlotInfo = { label: string, recovery: boolean, mem_kib: number, iterations: number, parallelism: number }
+
+function KeySlotsModal({ fp, onClose, onToast }: { fp: string, onClose: () => void, onToast: (t: string, k?: any) => void }) {
+  const { t } = useI18n()
+  const ref = React.useRef<HTMLDivElement>(null)
+  useFocusTrap(ref, true)
+  const [slots, setSlots] = React.useState<SlotInfo[]>([])
+  const [current, setCurrent] = React.useState('')
+  const [label, setLabel] = React.useState('')
+  const [v1, setV1] = React.useState('')
+  const [v2, setV2] = React.useState('')
+  const [code, setCode] = React.useState('')
+  const [busy, setBusy] = React.useState(false)
+  const load = React.useCallback(async () => {
+    try { setSlots(await invoke<SlotInfo[]>('list_key_slots', { fingerprint: fp })) } catch (e: any) { onToast(t('failedPrefix') + String(e), 'error') }
+  }, [fp])
+  React.useEffect(() => { load() }, [load])
+  // every change needs a secret that already opens the master
+  async function run(cmd: string, args: Record<string, unknown>, toast: string) {
+    setBusy(true)
+    try {
+      const out = await invoke<string | null>(cmd, { viewerPassword: current, fingerprint: fp, ...args })
+      if (cmd === 'add_recovery_code' && out) setCode(out)
+      onToast(toast, 'success')
+      await load()
+      return true
+    } catch (e: any) { onToast(t('failedPrefix') + String(e), 'error'); return false }
+    finally { setBusy(false) }
+  }
+  return (
+    <div className="modal" role="dialog" aria-modal="true" aria-labelledby="key-slots-title" onClick={e => e.stopPropagation()} ref={ref}>
+      <h3 id="key-slots-title" className="card-title">{t('keySlots')}</h3>
+      <p className="muted" style={{ marginTop: 0 }}>{t('keySlotsHelp')}</p>
+      <div className="col">
+        <div className="badge" title={fp} style={{ alignSelf: 'flex-start' }}>{shortFp(fp)}</div>
+        <label>{t('currentViewerPassword')}</label>
+        <input type="password" value={current} onChange={e => setCurrent(e.target.value)} autoFocus />
+      </div>
+      <div className="col" style={{ gap: 6, marginTop: 8 }}>
+        {slots.map(s => (
+          <div key={s.label} className="row" style={{ justifyContent: 'space-between', alignItems: 'center' }}>
+            <span>{s.label} {s.recovery ? <span className="badge">{t('recoveryCode')}</span> : <span className="muted" style={{ fontSize: 12 }}>Argon2id · {Math.round(s.mem_kib / 1024)} MiB · {s.iterations}×</span>}</span>
+            <button className="btn small danger" disabled={busy || !current} onClick={async () => { if (confirm(t('confirmRemoveSlot'))) await run('remove_key_slot', { label: s.label }, t('toastSlotRemoved')) }}>{t('del')}</button>
+          </div>
+        ))}
+      </div>
+      <div className="col" style={{ marginTop: 8 }}>
+        <label>{t('slotLabel')}</label>
+        <input type="text" value={label} onChange={e => setLabel(e.target.value)} maxLength={64} autoComplete="off" spellCheck={false} />
+        <label>{t('newViewerPassword')}</label>
+        <input type="password" value={v1} onChange={e => setV1(e.target.value)} />
+        <label>{t('confirmViewer')}</label>
+        <input type="password" value={v2} onChange={e => setV2(e.target.value)} />
+      </div>
+      {code && <div style={{ marginTop: 8 }}><RecoveryCode code={code} /></div>}
+      <div className="row" style={{ marginTop: 8 }}>
+        <button className="btn primary" disabled={busy || !current || !label.trim() || !v1 || v1 !== v2} aria-busy={busy ? 'true' : 'false'} onClick={async () => {
+          if (await run('add_key_slot', { label: label.trim(), newViewerPassword: v1 }, t('toastSlotAdded'))) { setLabel(''); setV1(''); setV2('') }
+        }}>{t('addSlot')}</button>
+        <button className="btn" disabled={busy || !current} onClick={() => run('add_recovery_code', {}, t('toastRecoveryCodeCreated'))}>{t('newRecoveryCode')}</button>
+        <button className="btn" disabled={busy || !current} title={t('strengthenKdfHelp')} onClick={() => run('upgrade_master_kdf', {}, t('toastKdfUpgraded'))}>{t('strengthenKdf')}</button>
+        <button className="btn" onClick={onClose}>{t('close')}</button>
+      </div>
+    </div>
+  )
+}
+
+function ChangeViewerModal({ fp, onClose, onToast }: { fp: string, onClose: () => void, onToast: (t: string, k?: any) => void }) {
+  const { t } = useI18n()
+  const ref = React.useRef<HTMLDivElement>(null)
+  useFocusTrap(ref, true)
+  const [current, setCurrent] = React.useState('')
+  const [v1, setV1] = React.useState('')
+  const [v2, setV2] = React.useState('')
+  const [busy, setBusy] = React.useState(false)
+  return (
+    <div className="modal" role="dialog" aria-modal="true" aria-labelledby="change-viewer-title" onClick={e => e.stopPropagation()} ref={ref}>
+      <h3 id="change-viewer-title" className="card-title">{t('changeViewer')}</h3>
+      <p className="muted" style={{ marginTop: 0 }}>{t('changeViewerHelp')}</p>
+      <div className="col">
+        <div className="badge" title={fp} style={{ alignSelf: 'flex-start' }}>{shortFp(fp)}</div>
+        <label>{t('currentViewerPassword')}</label>
+        <input type="password" value={current} onChange={e => setCurrent(e.target.value)} autoFocus />
+        <label>{t('newViewerPassword')}</label>
+        <input type="password" value={v1} onChange={e => setV1(e.target.value)} />
+        <label>{t('confirmViewer')}</label>
+        <input type="password" value={v2} onChange={e => setV2(e.target.value)} />
+      </div>
+      <div className="row" style={{ marginTop: 8 }}>
+        <button className="btn primary" disabled={busy || !current || !v1 || v1 !== v2} aria-busy={busy ? 'true' : 'false'} onClick={async () => {
+          setBusy(true)
+          try {
+            await invoke('change_viewer_password', { oldViewerPassword: current, newViewerPassword: v1, fingerprint: fp })
+            onToast(t('toastViewerChanged'), 'success'); onClose()
+          } catch (e: any) { onToast(t('failedPrefix') + String(e), 'error') }
+          finally { setBusy(false) }
+        }}>{busy ? t('saving') : t('save')}</button>
+        <button className="btn" onClick={onClose}>{t('close')}</button>
+      </div>
+    </div>
+  )
+}
+
 function SettingsDrawer(props: any) {
   const { t } = useI18n()
   const ref = React.useRef<HTMLDivElement>(null)
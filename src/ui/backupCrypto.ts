@@ -9,6 +9,13 @@ export type BackupEntry = {
   created_at: number
   order?: number
   fingerprint?: string | null
+  policy?: Record<string, unknown> | null
+  counter?: number
+  previous_counter?: number | null
+  lesspass?: Record<string, unknown> | null
+  spectre?: Record<string, unknown> | null
+  otp?: Record<string, unknown> | null
+  security_questions?: { question: string, style?: string }[]
 }
 
 export type BackupFile = { entries: BackupEntry[] }
@@ -115,32 +122,86 @@ export async function parseBackupBytes(bytes: Uint8Array, passphrase?: string):
   throw new Error('invalid backup content')
 }
 
+// Same layout as backup.rs: columns are found by header name and settings that change
+// the derived password are JSON cells
+const CSV_COLUMNS = [
+  'fingerprint', 'label', 'postfix', 'method_id', 'created_at', 'id',
+  'policy', 'counter', 'previous_counter', 'lesspass', 'spectre', 'security_questions',
+]
+
+function csvCell(s: unknown): string {
+  const str = String(s ?? '')
+  return /[",\r\n]/.test(str) ? `"${str.replace(/"/g, '""')}"` : str
+}
+
+function jsonCell(v: unknown): string { return v == null ? '' : JSON.stringify(v) }
+
+function parseJsonCell(cell: string | undefined, label: string, column: string) {
+  if (!cell || !cell.trim()) return null
+  try { return JSON.parse(cell) } catch { throw new Error(`entry "${label}": bad ${column} column`) }
+}
+
+// RFC 4180 records: quoted cells may hold commas, doubled quotes and line breaks
+function parseCsv(raw: string): string[][] {
+  const rows: string[][] = []
+  let row: string[] = [], cell = '', quoted = false
+  for (let i = 0; i < raw.length; i++) {
+    const c = raw[i]
+    if (quoted) {
+      if (c === '"' && raw[i + 1] === '"') { cell += '"'; i++ } else if (c === '"') quoted = false
+      else cell += c
+    } else if (c === '"') quoted = true
+    else if (c === ',') { row.push(cell); cell = '' }
+    else if (c === '\n') { row.push(cell); rows.push(row); row = []; cell = '' }
+    else if (c !== '\r') cell += c
+  }
+  if (cell || row.length) { row.push(cell); rows.push(row) }
+  return rows.filter(r => r.some(c => c.trim().length > 0))
+}
+
 export function buildCsv(entries: BackupEntry[]) {
-  const lines = ['fingerprint,label,postfix,method_id,created_at,id']
+  const lines = [CSV_COLUMNS.join(',')]
   for (const e of entries) {
-    const fp = e.fingerprint || ''
-    const safe = (s: string) => String(s ?? '').replace(/"/g, '""')
-    lines.push([fp, safe(e.label), safe(e.postfix), e.method_id, e.created_at, e.id].join(','))
+    // a sealed OTP secret is bound to its entry and master; only the encrypted backup keeps it
+    if (e.otp) throw new Error(`entry "${e.label}" has a one-time code secret, which CSV cannot carry; use an encrypted backup`)
+    const cells = [
+      e.fingerprint || '', e.label, e.postfix, e.method_id, e.created_at, e.id,
+      jsonCell(e.policy), e.counter ?? 0, e.previous_counter ?? '', jsonCell(e.lesspass), jsonCell(e.spectre),
+      jsonCell(e.security_questions?.length ? e.security_questions : null),
+    ]
+    lines.push(cells.map(csvCell).join(','))
   }
   return lines.join('\n')
 }
 
 export function parseCsvEntries(raw: string): BackupEntry[] {
-  const lines = raw.split(/\r?\n/).filter(l => l.trim().length > 0)
+  const [header = [], ...rows] = parseCsv(raw)
+  const names = header.map(h => h.trim())
   const entries: BackupEntry[] = []
-  lines.forEach((line, idx) => {
-    if (idx === 0) return
-    const parts = line.split(',')
+  rows.forEach((parts, idx) => {
     if (parts.length < 6) return
-    const [fingerprint, label, postfix, method_id, created_at, id] = parts
+    const cell = (name: string) => { const i = names.indexOf(name); return i < 0 ? undefined : parts[i] }
+    const label = cell('label') || ''
+    const counter = (name: string) => {
+      const c = (cell(name) || '').trim()
+      if (!c) return null
+      if (!/^\d+$/.test(c)) throw new Error(`entry "${label}": bad ${name} column`)
+      return parseInt(c, 10)
+    }
     entries.push({
-      id: id || `${idx}`,
-      label: label || '',
-      postfix: postfix || '',
-      method_id: method_id || 'len36_strong',
-      created_at: parseInt(created_at || '0', 10) || 0,
+      id: cell('id') || `${idx + 1}`,
+      label,
+      postfix: cell('postfix') || '',
+      method_id: cell('method_id') || 'len36_strong',
+      created_at: parseInt(cell('created_at') || '0', 10) || 0,
       order: 0,
-      fingerprint: fingerprint || '',
+      fingerprint: cell('fingerprint') || '',
+      policy: parseJsonCell(cell('policy'), label, 'policy'),
+      counter: counter('counter') ?? 0,
+      previous_counter: counter('previous_counter'),
+      lesspass: parseJsonCell(cell('lesspass'), label, 'lesspass'),
+      spectre: parseJsonCell(cell('spectre'), label, 'spectre'),
+      security_questions: parseJsonCell(cell('security_questions'), label, 'security_questions') ?? [],
     })
   })
   return entries
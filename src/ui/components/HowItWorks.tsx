@@ -44,6 +44,11 @@ export function HowItWorks({ compact = false }: { compact?: boolean }) {
           <li>{t('aboutMethodsStrong')}</li>
           <li>{t('aboutMethodsLength')}</li>
           <li>{t('aboutMethodsLegacy')}</li>
+          <li>{t('aboutMethodsV3')}</li>
+          <li>{t('aboutMethodsArgon')}</li>
+          <li>{t('aboutMethodsPin')}</li>
+          <li>{t('aboutMethodsBip39')}</li>
+          <li>{t('aboutMethodsIdentity')}</li>
         </ul>
       </section>
 
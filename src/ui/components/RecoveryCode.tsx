@@ -0,0 +1,13 @@
+import React from 'react'
+import { useI18n } from '../i18n'
+
+// A freshly generated recovery code; the backend keeps only a key slot, so this is the one time it is shown
+export function RecoveryCode({ code }: { code: string }) {
+  const { t } = useI18n()
+  return (
+    <div className="col" style={{ gap: 8 }}>
+      <p className="muted" style={{ margin: 0 }}>{t('recoveryCodeHelp')}</p>
+      <div className="password" style={{ fontSize: 18, letterSpacing: 1, wordBreak: 'break-all', userSelect: 'all' }}>{code}</div>
+    </div>
+  )
+}
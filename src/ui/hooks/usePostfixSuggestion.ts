@@ -0,0 +1,23 @@
+import React from 'react'
+import { invoke } from '../../bridge'
+
+export type PostfixSuggestion = { canonical: string | null, matches: { id: string, label: string, postfix: string }[] }
+
+// Asks the backend for the registrable domain of what is typed (debounced) and which saved
+// entries share it; `null` while empty or when the postfix is already canonical and unused.
+export function usePostfixSuggestion(postfix: string) {
+  const [suggestion, setSuggestion] = React.useState<PostfixSuggestion | null>(null)
+  React.useEffect(() => {
+    if (!postfix.trim()) { setSuggestion(null); return }
+    let cancelled = false
+    const timer = window.setTimeout(async () => {
+      try {
+        const s = await invoke<PostfixSuggestion>('suggest_postfix', { input: postfix })
+        const useful = (s.canonical && s.canonical !== postfix) || s.matches.length > 0
+        if (!cancelled) setSuggestion(useful ? s : null)
+      } catch { if (!cancelled) setSuggestion(null) }
+    }, 250)
+    return () => { cancelled = true; window.clearTimeout(timer) }
+  }, [postfix])
+  return suggestion
+}
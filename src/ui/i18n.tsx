@@ -57,6 +57,15 @@ const bundle: Bundle = {
     aboutMethodsStrong: 'Strong: includes symbols for higher entropy; preferred when allowed.',
     aboutMethodsLength: 'Longer is stronger. Choose 20–36 for primary accounts; 10 for legacy constraints.',
     aboutMethodsLegacy: 'Legacy v1/v2 are for historical parity. Prefer modern strong methods for new entries.',
+    aboutMethodsV3: 'v3: HMAC‑SHA256 keyed with the master over length‑prefixed inputs, so a postfix can never be confused with another master/postfix split. Recommended for new entries.',
+    aboutMethodsArgon: 'Argon2id: each generate runs a memory‑hard hash (~19 MiB, 2 passes), so a leaked site password cannot be used to brute‑force the master at GPU speed. Expect a short delay, longer on phones.',
+    aboutMethodsPin: 'PIN: digits only, for bank cards, voicemail and door codes. The “safe” variants skip trivial PINs (1111, 1234, 9876, years 1900–2099) and otherwise give the same digits.',
+    aboutMethodsBip39: 'BIP39: a valid 12–24 word seed phrase (with checksum) per postfix, for reproducible test wallets. Anyone who sees it controls the wallet.',
+    aboutMethodsIdentity: 'Usernames and email aliases: every saved entry also gets a pronounceable username and, with an alias template set in Preferences, an email address. Both come from the master and postfix only, so they survive password rotation.',
+    methodBits: 'bits',
+    selfTestFailed: 'Self-test failed: this build derives different passwords than the reference vectors. Do not use it; reinstall from the official release.',
+    methodDeprecated: 'deprecated',
+    highValueMethodWarning: 'High-value secret: this seed phrase alone controls a wallet. Do not paste it into chats or keep it on the clipboard.',
     aboutUsingTitle: 'Using the app effectively',
     aboutUsingQuick: 'Quick generate: enter Postfix, pick Method, generate, copy. Optionally auto-save the entry.',
     aboutUsingSave: 'Saving: enable “Save this postfix” and set a short Label (e.g., Example).',
@@ -78,9 +87,9 @@ const bundle: Bundle = {
     aboutSecurityNoNetwork: 'No network dependency for generation—data stays on your device.',
     aboutBackupTitle: 'Backup and import',
     aboutBackupExport: 'Export a backup to a .safe file that contains only your saved entries (labels, postfixes, methods, timestamps, master fingerprints), never your master or viewer passwords. If you set a passphrase it encrypts the JSON inside using Argon2id + ChaCha20‑Poly1305; without a passphrase it stays plain JSON, so prefer a strong passphrase and store the file on encrypted disk or another protected location.',
-    aboutBackupImport: 'Import a .safe file on the Backup tab to restore your Saved list on this device. Entries are tied to the master fingerprint they were created with, so first set up the same master password, then choose whether to merge with existing entries or enable Overwrite to fully replace them.',
+    aboutBackupImport: 'Import a .safe file on the Backup tab to restore your Saved list on this device. Entries are tied to a master fingerprint, which differs per device, so first set up the same master password, map the imported fingerprints to it, then choose whether to merge with existing entries or enable Overwrite to fully replace them.',
     aboutCsvTitle: 'CSV import/export',
-    aboutCsvNotes: 'Use CSV for one‑time migration or inspection. The file is plain text with columns “fingerprint,label,postfix,method_id,created_at,id”; you will map imported fingerprints to your local Masters during import. Treat CSV as sensitive (no encryption), keep it only as long as needed, and delete it afterward.',
+    aboutCsvNotes: 'Use CSV for one‑time migration or inspection. The file is plain text with columns “fingerprint,label,postfix,method_id,created_at,id”, then the counters, the policy, LessPass and Spectre settings and the security questions as JSON; you will map imported fingerprints to your local Masters during import. Treat CSV as sensitive (no encryption), keep it only as long as needed, and delete it afterward. Entries with a one‑time code secret cannot be exported to CSV; use an encrypted backup for them.',
     aboutLinksTitle: 'Links',
     aboutLinkSite: 'Website',
     aboutLinkRepo: 'Repository',
@@ -139,6 +148,10 @@ const bundle: Bundle = {
     holdOnlyReveal: 'Only hold-to-reveal (hide toggle button)',
     helpHoldOnlyReveal: 'Removes the toggle Reveal/Hide button; secrets are visible only while holding.',
     clearClipboardOnBlur: 'Clear clipboard on app blur/hidden',
+    aliasTemplate: 'Email alias template',
+    helpAliasTemplate: 'Saved entries show a per-site address built from this template, e.g. me+{alias}@example.com or {alias}@your-catch-all.domain. Leave empty to turn off.',
+    username: 'Username',
+    email: 'Email',
     helpClearOnBlur: 'Clears clipboard and hides output when the app loses focus or becomes hidden.',
     autoCloseIn: 'Auto-close in',
     generating: 'Generating…',
@@ -146,6 +159,8 @@ const bundle: Bundle = {
     // toasts & dialogs
     toastMasterSaved: 'Master password saved (encrypted by viewer password).',
     toastCopied: 'Copied to clipboard',
+    postfixSuggestion: 'Use the site domain',
+    postfixMatchesSaved: 'Saved for this site',
     toastCopyFailed: 'Copy failed. Please copy manually.',
     toastEntryDeleted: 'Entry deleted',
     toastEntryDeleteFailed: 'Failed to delete entry',
@@ -156,6 +171,7 @@ const bundle: Bundle = {
     toastMasterDeleted: 'Master deleted',
     toastMasterDeleteFailed: 'Delete failed',
     confirmDeleteMaster: 'Delete this master?',
+    confirmNormalizeMaster: 'This master contains characters that can be typed in two Unicode forms (for example accents on macOS vs Android). Store it normalized (NFC)? Recommended: OK. Cancel keeps it exactly as typed, which only matters for legacy and compatibility methods.',
     copy: 'Copy',
     pin: 'Pin',
     unpin: 'Unpin',
@@ -188,6 +204,8 @@ const bundle: Bundle = {
     import: 'Import',
     importedCountPrefix: 'Imported ',
     importedCountSuffix: ' entries',
+    importedUnmatchedPrefix: 'Entries for no master on this device: ',
+    importedUnmatchedSuffix: '. Ones from an older backup appear once their master is unlocked.',
     importFailedPrefix: 'Import failed: ',
     importHelp: 'Import merges with existing entries by default; choose Overwrite to replace all.',
     importHelpWithMapping: 'Import a .safe backup. If the viewer differs on this device, drag imported fingerprints to local masters — unmapped fingerprints are skipped.',
@@ -250,8 +268,29 @@ const bundle: Bundle = {
     viewerRepeatPlaceholder: 'Repeat viewer',
     // fingerprint
     fingerprintTitle: 'Master fingerprint',
-    fingerprintHelp: 'Enter the viewer password for this device to verify the current master password identity (MD5 fingerprint).',
-    fingerprintLabel: 'Fingerprint (MD5 of master):',
+    fingerprintHelp: 'Enter the viewer password for this device to verify the current master password identity (keyed fingerprint: salted per device and slow to compute, so it reveals nothing about the master).',
+    fingerprintLabel: 'Fingerprint:',
+    changeViewer: 'Change viewer password',
+    keySlots: 'Key slots',
+    keySlotsHelp: 'Each slot is another viewer password (or the recovery code) that opens this master. Removing one does not affect the others.',
+    slotLabel: 'Slot label',
+    addSlot: 'Add slot',
+    newRecoveryCode: 'New recovery code',
+    recoveryCode: 'Recovery code',
+    recoveryCodeHelp: 'Print it or write it down and keep it offline. It opens this master like a viewer password and is shown only this once; a new code replaces the old one.',
+    recoveryCodeSaved: 'I have saved it',
+    setupRecoveryCode: 'Also create a printable recovery code',
+    confirmRemoveSlot: 'Remove this key slot?',
+    toastSlotAdded: 'Key slot added',
+    toastSlotRemoved: 'Key slot removed',
+    toastRecoveryCodeCreated: 'Recovery code created',
+    strengthenKdf: 'Strengthen',
+    strengthenKdfHelp: 'Benchmarks this device and re-wraps the slot of the password entered above with Argon2id settings taking about a second to unlock',
+    toastKdfUpgraded: 'Key derivation strengthened',
+    changeViewerHelp: 'Re-encrypts this master under a new viewer password. The master, its fingerprint and every generated password stay the same.',
+    currentViewerPassword: 'Current viewer password',
+    newViewerPassword: 'New viewer password',
+    toastViewerChanged: 'Viewer password changed',
     viewMaster: 'View master',
     revealMaster: 'Reveal master with viewer',
     copyMaster: 'Copy master',
@@ -318,6 +357,15 @@ const bundle: Bundle = {
     aboutMethodsStrong: 'Strong: добавлены символы — больше энтропии; предпочтительно, если форма разрешает.',
     aboutMethodsLength: 'Длина важна: 20–36 для основных аккаунтов; 10 — для старых ограничений.',
     aboutMethodsLegacy: 'Legacy v1/v2 — для исторической совместимости. Для новых записей используйте современные strong‑методы.',
+    aboutMethodsV3: 'v3: HMAC‑SHA256 с ключом‑мастером по входам с префиксом длины — постфикс нельзя спутать с другим разбиением мастер/постфикс. Рекомендуется для новых записей.',
+    aboutMethodsArgon: 'Argon2id: каждая генерация выполняет memory‑hard хеш (~19 МиБ, 2 прохода), поэтому утёкший пароль сайта нельзя использовать для перебора мастера на GPU. Ожидайте небольшую задержку, на телефонах дольше.',
+    aboutMethodsPin: 'PIN: только цифры — для банковских карт, голосовой почты и кодовых замков. Варианты «safe» пропускают тривиальные PIN (1111, 1234, 9876, годы 1900–2099), в остальном цифры те же.',
+    aboutMethodsBip39: 'BIP39: корректная сид-фраза из 12–24 слов (с контрольной суммой) для каждого постфикса — для воспроизводимых тестовых кошельков. Кто её увидит, тот управляет кошельком.',
+    aboutMethodsIdentity: 'Имена пользователей и email‑алиасы: каждая сохранённая запись также получает произносимое имя пользователя и, если в настройках задан шаблон алиаса, адрес email. Оба зависят только от мастера и постфикса и не меняются при ротации пароля.',
+    methodBits: 'бит',
+    selfTestFailed: 'Самопроверка не пройдена: эта сборка выдаёт пароли, отличные от эталонных. Не пользуйтесь ей; переустановите официальный релиз.',
+    methodDeprecated: 'устаревший',
+    highValueMethodWarning: 'Секрет высокой ценности: одна эта сид-фраза даёт полный доступ к кошельку. Не вставляйте её в чаты и не оставляйте в буфере обмена.',
     aboutUsingTitle: 'Эффективное использование',
     aboutUsingQuick: 'Быстрая генерация: введите Постфикс, выберите Метод, сгенерируйте и скопируйте. Можно сразу сохранить запись.',
     aboutUsingSave: 'Сохранение: включите «Сохранить постфикс» и задайте короткую Метку (например, Example).',
@@ -339,9 +387,9 @@ const bundle: Bundle = {
     aboutSecurityNoNetwork: 'Генерация офлайн — данные остаются на вашем устройстве.',
     aboutBackupTitle: 'Резервное копирование и импорт',
     aboutBackupExport: 'Экспорт бэкапа в файл .safe, который содержит только сохранённые записи (метки, постфиксы, методы, время создания и отпечатки мастера), но не содержит сам мастер‑пароль или viewer‑пароль. При задании пароля JSON внутри шифруется (Argon2id + ChaCha20‑Poly1305); без пароля остаётся открытым JSON, поэтому обычно лучше использовать сильный пароль и хранить файл в защищённом месте (зашифрованный диск, «безопасное» облако, флешка).',
-    aboutBackupImport: 'Импортируйте .safe на вкладке «Резервное копирование», чтобы восстановить список Сохранённых на этом устройстве. Записи привязаны к отпечатку того мастера, под которым они были созданы, поэтому сначала настройте тот же мастер‑пароль, затем выберите — добавить к текущим записям или включить «Перезаписать» для полной замены.',
+    aboutBackupImport: 'Импортируйте .safe на вкладке «Резервное копирование», чтобы восстановить список Сохранённых на этом устройстве. Записи привязаны к отпечатку мастера, который на каждом устройстве свой, поэтому сначала настройте тот же мастер‑пароль, сопоставьте ему импортированные отпечатки, затем выберите — добавить к текущим записям или включить «Перезаписать» для полной замены.',
     aboutCsvTitle: 'CSV импорт/экспорт',
-    aboutCsvNotes: 'CSV удобно использовать для разовой миграции или просмотра. Файл — это открытый текст с колонками «fingerprint,label,postfix,method_id,created_at,id»; при импорте вы сопоставляете импортированные отпечатки локальным Мастерам. Относитесь к CSV как к секрету (шифрования нет), держите его только на время миграции и удаляйте после использования.',
+    aboutCsvNotes: 'CSV удобно использовать для разовой миграции или просмотра. Файл — это открытый текст с колонками «fingerprint,label,postfix,method_id,created_at,id», затем счётчики, настройки политики, LessPass и Spectre и контрольные вопросы в JSON; при импорте вы сопоставляете импортированные отпечатки локальным Мастерам. Относитесь к CSV как к секрету (шифрования нет), держите его только на время миграции и удаляйте после использования. Записи с секретом одноразовых кодов в CSV не экспортируются — для них используйте зашифрованную копию.',
     aboutLinksTitle: 'Ссылки',
     aboutLinkSite: 'Сайт',
     aboutLinkRepo: 'Репозиторий',
@@ -400,6 +448,10 @@ const bundle: Bundle = {
     holdOnlyReveal: 'Только удерживание для показа (без кнопки Показать/Скрыть)',
     helpHoldOnlyReveal: 'Убирает кнопку Показать/Скрыть; секрет виден лишь во время удерживания.',
     clearClipboardOnBlur: 'Очищать буфер при сворачивании/потере фокуса',
+    aliasTemplate: 'Шаблон email‑алиаса',
+    helpAliasTemplate: 'Для сохранённых записей показывается отдельный адрес по этому шаблону, например me+{alias}@example.com или {alias}@ваш-catch-all.домен. Оставьте пустым, чтобы отключить.',
+    username: 'Имя пользователя',
+    email: 'Email',
     helpClearOnBlur: 'Очищает буфер обмена и скрывает вывод при потере фокуса или скрытии окна.',
     autoCloseIn: 'Автозакрытие через',
     generating: 'Генерация…',
@@ -407,6 +459,8 @@ const bundle: Bundle = {
     // toasts & dialogs
     toastMasterSaved: 'Мастер‑пароль сохранён (зашифрован viewer‑паролем).',
     toastCopied: 'Скопировано в буфер обмена',
+    postfixSuggestion: 'Использовать домен сайта',
+    postfixMatchesSaved: 'Сохранено для этого сайта',
     toastCopyFailed: 'Не удалось скопировать. Скопируйте вручную.',
     toastEntryDeleted: 'Запись удалена',
     toastEntryDeleteFailed: 'Не удалось удалить запись',
@@ -417,6 +471,7 @@ const bundle: Bundle = {
     toastMasterDeleted: 'Мастер удалён',
     toastMasterDeleteFailed: 'Не удалось удалить',
     confirmDeleteMaster: 'Удалить этот мастер?',
+    confirmNormalizeMaster: 'Мастер содержит символы, которые можно набрать в двух формах Unicode (например, диакритика на macOS и Android). Сохранить его в нормализованной форме (NFC)? Рекомендуется: OK. «Отмена» сохранит его как введено — это важно только для legacy‑методов и методов совместимости.',
     copy: 'Копировать',
     pin: 'Закрепить',
     unpin: 'Открепить',
@@ -452,6 +507,8 @@ const bundle: Bundle = {
     import: 'Импорт',
     importedCountPrefix: 'Импортировано записей: ',
     importedCountSuffix: '',
+    importedUnmatchedPrefix: 'Записей без мастера на этом устройстве: ',
+    importedUnmatchedSuffix: '. Записи из старой копии появятся после разблокировки их мастера.',
     importFailedPrefix: 'Ошибка импорта: ',
     importHelp: 'По умолчанию импорт дополняет текущие записи; включите Перезаписать для полной замены.',
     importHelpWithMapping: 'Импортируйте .safe‑архив. Если viewer на этом устройстве другой, сопоставьте каждый отпечаток с локальным мастером — несопоставленные отпечатки будут пропущены.',
@@ -514,8 +571,29 @@ const bundle: Bundle = {
     viewerRepeatPlaceholder: 'Повторите viewer',
     // fingerprint
     fingerprintTitle: 'Отпечаток мастера',
-    fingerprintHelp: 'Введите viewer‑пароль этого устройства, чтобы проверить текущую идентичность мастер‑пароля (MD5 отпечаток).',
-    fingerprintLabel: 'Отпечаток (MD5 от мастера):',
+    fingerprintHelp: 'Введите viewer‑пароль этого устройства, чтобы проверить текущую идентичность мастер‑пароля (отпечаток с ключом: своя соль на каждом устройстве и медленное вычисление, поэтому он ничего не раскрывает о мастере).',
+    fingerprintLabel: 'Отпечаток:',
+    changeViewer: 'Сменить viewer‑пароль',
+    keySlots: 'Слоты ключей',
+    keySlotsHelp: 'Каждый слот — ещё один viewer‑пароль (или код восстановления), открывающий этот мастер. Удаление одного не затрагивает остальные.',
+    slotLabel: 'Название слота',
+    addSlot: 'Добавить слот',
+    newRecoveryCode: 'Новый код восстановления',
+    recoveryCode: 'Код восстановления',
+    recoveryCodeHelp: 'Распечатайте или запишите его и храните офлайн. Он открывает этот мастер как viewer‑пароль и показывается только сейчас; новый код заменяет старый.',
+    recoveryCodeSaved: 'Я сохранил код',
+    setupRecoveryCode: 'Также создать печатный код восстановления',
+    confirmRemoveSlot: 'Удалить этот слот ключа?',
+    toastSlotAdded: 'Слот ключа добавлен',
+    toastSlotRemoved: 'Слот ключа удалён',
+    toastRecoveryCodeCreated: 'Код восстановления создан',
+    strengthenKdf: 'Усилить',
+    strengthenKdfHelp: 'Измеряет скорость устройства и перешифровывает слот введённого выше пароля с настройками Argon2id, дающими разблокировку примерно за секунду',
+    toastKdfUpgraded: 'Формирование ключа усилено',
+    changeViewerHelp: 'Перешифровывает этот мастер новым viewer‑паролем. Мастер, его отпечаток и все генерируемые пароли не меняются.',
+    currentViewerPassword: 'Текущий viewer‑пароль',
+    newViewerPassword: 'Новый viewer‑пароль',
+    toastViewerChanged: 'Viewer‑пароль изменён',
     viewMaster: 'Показать мастер‑пароль',
     revealMaster: 'Показать мастер через viewer',
     copyMaster: 'Скопировать мастер',
@@ -562,7 +640,7 @@ const bundle: Bundle = {
     aboutSec3: '复制需显式操作；支持自动清除剪贴板。',
     aboutBackupTitle: '备份与导入',
     aboutBackupExport: '将备份导出为 .safe 文件，其中只包含已保存条目（标签、后缀、方法、创建时间以及主密码指纹），不会包含主密码或 Viewer 密码本身。若设置口令，内部 JSON 会通过 Argon2id + ChaCha20‑Poly1305 加密；如果不设口令则为明文 JSON，因此推荐使用强口令，并将文件保存在受保护的位置（如加密磁盘、安全云盘或 U 盘）。',
-    aboutBackupImport: '在“备份”页从 .safe 文件导入，在本设备上恢复已保存列表。条目会绑定到它们创建时对应的主密码指纹，因此请先在新设备上配置相同的主密码，然后选择与现有条目合并，或启用“覆盖”以完全替换当前列表。',
+    aboutBackupImport: '在“备份”页从 .safe 文件导入，在本设备上恢复已保存列表。条目会绑定到主密码指纹，而指纹在每台设备上都不同，因此请先在新设备上配置相同的主密码并将导入的指纹映射到它，然后选择与现有条目合并，或启用“覆盖”以完全替换当前列表。',
     aboutCsvTitle: 'CSV 导入/导出',
     aboutCsvNotes: 'CSV 适合一次性迁移或检查内容。文件是明文文本，列为 “fingerprint,label,postfix,method_id,created_at,id”；导入时你需要将导入的指纹映射到本地主密码。CSV 不加密，请将其视为敏感数据，仅在迁移期间短暂保存，并在完成后删除。',
     aboutUsingTitle: '使用技巧',
@@ -674,6 +752,8 @@ const bundle: Bundle = {
     import: '导入',
     importedCountPrefix: '已导入 ',
     importedCountSuffix: ' 条',
+    importedUnmatchedPrefix: '没有对应本机主密码的条目：',
+    importedUnmatchedSuffix: '。旧备份中的条目会在其主密码解锁后出现。',
     importFailedPrefix: '导入失败：',
     importHelp: '默认合并导入；选择覆盖以替换全部。',
     importHelpWithMapping: '导入 .safe 备份。如果本设备的 Viewer 不同，请将每个导入的指纹拖拽到本地主密码上；未映射的指纹会被跳过。',
@@ -736,8 +816,29 @@ const bundle: Bundle = {
     viewerRepeatPlaceholder: '重复 Viewer',
     // fingerprint
     fingerprintTitle: '主密码指纹',
-    fingerprintHelp: '输入本设备的 Viewer 密码以验证当前主密码身份（MD5 指纹）。',
-    fingerprintLabel: '指纹（主密码 MD5）：',
+    fingerprintHelp: '输入本设备的 Viewer 密码以验证当前主密码身份（带密钥的指纹：每台设备单独加盐且计算缓慢，不会泄露主密码的任何信息）。',
+    fingerprintLabel: '指纹：',
+    changeViewer: '更改 Viewer 密码',
+    keySlots: '密钥槽',
+    keySlotsHelp: '每个槽都是另一个可以打开此主密码的 Viewer 密码（或恢复码）。删除其中一个不会影响其他槽。',
+    slotLabel: '槽名称',
+    addSlot: '添加槽',
+    newRecoveryCode: '新的恢复码',
+    recoveryCode: '恢复码',
+    recoveryCodeHelp: '请打印或抄写下来并离线保存。它可以像 Viewer 密码一样打开此主密码，且只显示这一次；新的恢复码会替换旧的。',
+    recoveryCodeSaved: '我已保存',
+    setupRecoveryCode: '同时创建可打印的恢复码',
+    confirmRemoveSlot: '删除此密钥槽？',
+    toastSlotAdded: '已添加密钥槽',
+    toastSlotRemoved: '已删除密钥槽',
+    toastRecoveryCodeCreated: '已创建恢复码',
+    strengthenKdf: '增强',
+    strengthenKdfHelp: '测试本设备的速度，并用解锁约需一秒的 Argon2id 参数重新包装上面所输入密码的槽',
+    toastKdfUpgraded: '密钥派生已增强',
+    changeViewerHelp: '使用新的 Viewer 密码重新加密此主密码。主密码、其指纹以及所有生成的密码都保持不变。',
+    currentViewerPassword: '当前 Viewer 密码',
+    newViewerPassword: '新的 Viewer 密码',
+    toastViewerChanged: 'Viewer 密码已更改',
     viewMaster: '查看主密码',
     revealMaster: '使用 Viewer 查看主密码',
     copyMaster: '复制主密码',
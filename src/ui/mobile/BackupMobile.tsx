@@ -177,9 +177,10 @@ export function BackupMobile({ onToast, onImported, section, onBack = () => {} }
     if (!mapped.length) { onToast(t('nothingMapped') || 'Nothing mapped', 'error'); return }
     try {
       setImportBusy(true)
-      const count = await invoke<number>('import_entries_payload', { entries: mapped, overwrite: importOverwrite })
+      const { imported, unmatched } = await invoke<{ imported: number, unmatched: number }>('import_entries_payload', { entries: mapped, overwrite: importOverwrite })
       onImported()
-      onToast(`${t('importedCountPrefix')}${count}${t('importedCountSuffix')}`, 'success')
+      onToast(`${t('importedCountPrefix')}${imported}${t('importedCountSuffix')}`, 'success')
+      if (unmatched) onToast(`${t('importedUnmatchedPrefix')}${unmatched}${t('importedUnmatchedSuffix')}`, 'info')
       setImportPreview(null); setImportFile(null); setImportPass(''); setMappingModal(false)
     } catch (err: any) { onToast(t('importFailedPrefix') + String(err), 'error') } finally { setImportBusy(false) }
   }
@@ -5,8 +5,10 @@ import { FocusModal } from '../components/FocusModal'
 import { emit, on } from '../events'
 import { useFocusTrap } from '../a11y'
 import { useI18n } from '../i18n'
+import { usePostfixSuggestion } from '../hooks/usePostfixSuggestion'
 
 type Entry = { id: string; label: string; postfix: string; method_id: string; created_at: number; order?: number }
+type SavedOutput = { password: string; username: string; email: string | null }
 
 function shortMethod(id: string): string {
   if (id.startsWith('legacy')) return 'legacy'
@@ -16,7 +18,7 @@ function shortMethod(id: string): string {
 }
 
 export function MobileUnified({ methods, defaultMethod, autosaveQuick, blocked, autoClearSeconds, outputClearSeconds = 60, viewerPromptTimeoutSeconds = 30, copyOnConsoleGenerate = false, showPostfix = false, holdOnlyReveal = false, clearClipboardOnBlur = false, extendSeconds = 30, onToast, setDefaultMethod, setAutoClearSeconds, setMaskSensitive, setAutosaveQuick, onImported }: {
-  methods: { id: string; name: string }[],
+  methods: { id: string; name: string; highValue?: boolean }[],
   defaultMethod: string,
   autosaveQuick: boolean,
   blocked: boolean,
@@ -39,10 +41,13 @@ export function MobileUnified({ methods, defaultMethod, autosaveQuick, blocked,
   const [search, setSearch] = React.useState('')
   const [method, setMethod] = React.useState(defaultMethod)
   const [postfix, setPostfix] = React.useState('')
+  const suggestion = usePostfixSuggestion(postfix)
   const [save, setSave] = React.useState(autosaveQuick)
   const [label, setLabel] = React.useState('')
   const [busy, setBusy] = React.useState(false)
   const [output, setOutput] = React.useState<string | null>(null)
+  // account identity shown under a saved entry's password; cleared with a quick generate
+  const [identity, setIdentity] = React.useState<{ username: string; email: string | null } | null>(null)
   const [revealed, setRevealed] = React.useState(false)
   const outputTimer = React.useRef<number | null>(null)
   const [resultOpen, setResultOpen] = React.useState(false)
@@ -189,6 +194,7 @@ export function MobileUnified({ methods, defaultMethod, autosaveQuick, blocked,
       }
       const pw = await invoke<string>('generate_password', { viewerPassword, postfix: cleanPostfix, methodId: method })
       setOutputWithAutoClear(pw)
+      setIdentity(null)
       if (copyOnConsoleGenerate) { await copy(pw) }
       if (save) {
         const lbl = lblSan || deriveLabelFromPostfix(cleanPostfix)
@@ -207,9 +213,10 @@ export function MobileUnified({ methods, defaultMethod, autosaveQuick, blocked,
     if (!viewerPassword) return
     setBusy(true)
     try {
-      const pw = await invoke<string>('generate_saved', { id, viewerPassword })
-      setOutputWithAutoClear(pw)
-      try { await invoke('write_clipboard_native', { text: pw }) } catch {}
+      const out = await invoke<SavedOutput>('generate_saved', { id, viewerPassword })
+      setOutputWithAutoClear(out.password)
+      setIdentity({ username: out.username, email: out.email })
+      try { await invoke('write_clipboard_native', { text: out.password }) } catch {}
       onToast(t('toastCopied'), 'success')
       scheduleClipboardClear()
     } catch (err: any) {
@@ -452,10 +459,19 @@ export function MobileUnified({ methods, defaultMethod, autosaveQuick, blocked,
             <div className="col" style={{ gap: 8 }}>
               <label>{t('postfix')}</label>
               <input aria-label={t('postfix')} type="text" value={postfix} onChange={e => setPostfix(e.target.value)} placeholder={t('postfixPlaceholder')} spellCheck={false} autoCorrect="off" autoCapitalize="none" autoComplete="off" maxLength={256} />
+              {suggestion && (
+                <div className="muted" role="note">
+                  {suggestion.canonical && suggestion.canonical !== postfix && (
+                    <span>{t('postfixSuggestion')} <button className="btn small" onClick={() => setPostfix(suggestion.canonical!)}>{suggestion.canonical}</button></span>
+                  )}
+                  {suggestion.matches.length > 0 && <span style={{ marginLeft: 6 }}>{t('postfixMatchesSaved')}: {suggestion.matches.map(m => m.label).join(', ')}</span>}
+                </div>
+              )}
               <label>{t('method')}</label>
               <select aria-label={t('method')} value={method} onChange={e => setMethod(e.target.value)}>
                 {methods.map(m => <option key={m.id} value={m.id}>{m.name}</option>)}
               </select>
+              {methods.find(m => m.id === method)?.highValue && <div className="muted" role="note" style={{ color: 'var(--warn)' }}>{t('highValueMethodWarning')}</div>}
               <div className="row">
                 <input id="save-mb" type="checkbox" checked={save} onChange={e => { setSave(e.target.checked); if (e.target.checked && !label && postfix) setLabel(deriveLabelFromPostfix(postfix)) }} />
                 <label htmlFor="save-mb">{t('save')}</label>
@@ -520,6 +536,12 @@ export function MobileUnified({ methods, defaultMethod, autosaveQuick, blocked,
                   </button>
                 </div>
               </div>
+              {identity && (
+                <div className="col" style={{ gap: 4, color: 'var(--muted)' }}>
+                  <div>{t('username')}: <span className="password">{identity.username}</span></div>
+                  {identity.email && <div>{t('email')}: <span className="password">{identity.email}</span></div>}
+                </div>
+              )}
               <div className="col" style={{ gap: 6 }}>
                 <div className={`progress ${outPct >= 80 ? 'danger' : (outPct >= 60 ? 'warn' : '')}`} role="progressbar" aria-valuemin={0} aria-valuemax={100} aria-valuenow={Math.round(outPct)}>
                   <div className="bar" style={{ width: `${outPct}%` }}></div>
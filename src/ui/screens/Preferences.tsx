@@ -24,6 +24,7 @@ export function Preferences({ methods, defaultMethod, autoClearSeconds, maskSens
   // Rename local state to avoid any accidental global name collisions in bundlers
   const [holdOnlyRevealPref, setHoldOnlyRevealPref] = React.useState(false)
   const [clearClipboardOnBlur, setClearClipboardOnBlur] = React.useState(false)
+  const [aliasTemplate, setAliasTemplate] = React.useState('')
   const defaultHelpId = React.useId()
   const maskHelpId = React.useId()
   const autoHelpId = React.useId()
@@ -40,6 +41,7 @@ export function Preferences({ methods, defaultMethod, autoClearSeconds, maskSens
         if (typeof p?.output_extend_seconds === 'number') setExtendSecondsPref(p.output_extend_seconds)
         if (typeof p?.hold_only_reveal === 'boolean') setHoldOnlyRevealPref(!!p.hold_only_reveal)
         if (typeof p?.clear_clipboard_on_blur === 'boolean') setClearClipboardOnBlur(!!p.clear_clipboard_on_blur)
+        setAliasTemplate(p?.alias_template || '')
       } catch {}
     })()
   }, [])
@@ -192,6 +194,14 @@ export function Preferences({ methods, defaultMethod, autoClearSeconds, maskSens
         </select>
       </div>
       <p className="muted">{t('helpClearOnBlur')}</p>
+      <div className="row" style={{ marginTop: 8 }}>
+        <label>{t('aliasTemplate')}</label>
+        <input placeholder="me+{alias}@example.com" value={aliasTemplate} onChange={(e) => setAliasTemplate(e.target.value)} onBlur={async () => {
+          const v = aliasTemplate.trim()
+          try { await invoke('set_prefs', { aliasTemplate: v }) } catch (err:any) { onToast(String(err), 'error') }
+        }} />
+      </div>
+      <p className="muted">{t('helpAliasTemplate')}</p>
     </div>
   )
 }
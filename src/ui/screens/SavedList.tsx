@@ -13,6 +13,7 @@ type Entry = {
   method_id: string
   created_at: number
 }
+type SavedOutput = { password: string; username: string; email: string | null }
 
 export function SavedList({ methods, defaultMethod, blocked, onToast }: {
   methods: { id: string; name: string }[],
@@ -98,9 +99,9 @@ export function SavedList({ methods, defaultMethod, blocked, onToast }: {
     if (!viewerPassword) return
     setBusy(true)
     try {
-      const pw = await invoke<string>('generate_saved', { id, viewerPassword })
-      try { await invoke('write_clipboard_native', { text: pw }) } catch {}
-      onToast(t('toastCopied'), 'success')
+      const out = await invoke<SavedOutput>('generate_saved', { id, viewerPassword })
+      try { await invoke('write_clipboard_native', { text: out.password }) } catch {}
+      onToast(`${t('toastCopied')} · ${t('username')}: ${out.username}${out.email ? ` · ${t('email')}: ${out.email}` : ''}`, 'success')
       scheduleClipboardClear()
     } catch (err: any) { onToast(t('failedPrefix') + String(err), 'error') }
     finally { setBusy(false); setPwModal({ id: '', open: false }); setPwModalViewer('') }
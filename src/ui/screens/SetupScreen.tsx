@@ -3,7 +3,7 @@ import { PasswordInput } from '../PasswordInput'
 import { useI18n } from '../i18n'
 import { invoke } from '../../bridge'
 
-export type SetupState = { master: string; master2: string; viewer: string; viewer2: string }
+export type SetupState = { master: string; master2: string; viewer: string; viewer2: string; recovery?: boolean }
 
 export function SetupScreen({ state, setState, busy, error, onSubmit }: {
   state: SetupState,
@@ -85,6 +85,10 @@ export function SetupScreen({ state, setState, busy, error, onSubmit }: {
               {viewerMismatch && <span id={viewerErrId} className="muted" style={{ color: 'var(--danger)' }} aria-live="polite">{t('viewerMismatch') || 'Viewer passwords do not match'}</span>}
               {!viewerMismatch && viewerHasInv && <span className="muted" style={{ color: 'var(--danger)' }} aria-live="assertive">{t('warnInvisibleChars') || 'Input contains invisible/unsupported characters.'}</span>}
             </div>
+            <div className="opt">
+              <input id="setup-recovery" type="checkbox" checked={!!state.recovery} onChange={e => setState({ ...state, recovery: e.target.checked })} />
+              <label htmlFor="setup-recovery">{t('setupRecoveryCode')}</label>
+            </div>
           </section>
           {error && <div role="alert" aria-live="assertive" className="muted" style={{ color: 'var(--danger)' }}>{error}</div>}
           <div className="row" style={{ marginTop: 12, justifyContent: 'space-between', alignItems: 'center', gap: 8 }}>
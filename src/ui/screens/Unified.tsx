@@ -4,11 +4,13 @@ import { ViewerPrompt } from '../components/ViewerPrompt'
 import { emit, on } from '../events'
 import { useI18n } from '../i18n'
 import { useIsMobile } from '../hooks/useIsMobile'
+import { usePostfixSuggestion } from '../hooks/usePostfixSuggestion'
 
 type Entry = { id: string; label: string; postfix: string; method_id: string; created_at: number; order?: number }
+type SavedOutput = { password: string; username: string; email: string | null }
 
 export function Unified({ methods, defaultMethod, autosaveQuick, blocked, autoClearSeconds, outputClearSeconds = 60, viewerPromptTimeoutSeconds = 30, copyOnConsoleGenerate = false, showPostfix = false, holdOnlyReveal = false, clearClipboardOnBlur = false, extendSeconds = 30, onToast }: {
-  methods: { id: string; name: string }[],
+  methods: { id: string; name: string; highValue?: boolean }[],
   defaultMethod: string,
   autosaveQuick: boolean,
   blocked: boolean,
@@ -26,6 +28,7 @@ export function Unified({ methods, defaultMethod, autosaveQuick, blocked, autoCl
   const [search, setSearch] = React.useState('')
   const [method, setMethod] = React.useState(defaultMethod)
   const [postfix, setPostfix] = React.useState('')
+  const suggestion = usePostfixSuggestion(postfix)
   const [save, setSave] = React.useState(autosaveQuick)
   const [label, setLabel] = React.useState('')
   const [busy, setBusy] = React.useState(false)
@@ -187,9 +190,9 @@ export function Unified({ methods, defaultMethod, autosaveQuick, blocked, autoCl
     if (!viewerPassword) return
     setBusy(true)
     try {
-      const pw = await invoke<string>('generate_saved', { id, viewerPassword })
-      try { await invoke('write_clipboard_native', { text: pw }) } catch {}
-      onToast(t('toastCopied'), 'success')
+      const out = await invoke<SavedOutput>('generate_saved', { id, viewerPassword })
+      try { await invoke('write_clipboard_native', { text: out.password }) } catch {}
+      onToast(`${t('toastCopied')} · ${t('username')}: ${out.username}${out.email ? ` · ${t('email')}: ${out.email}` : ''}`, 'success')
       scheduleClipboardClear()
     } catch (err: any) {
       const msg = String(err || '')
@@ -304,7 +307,17 @@ export function Unified({ methods, defaultMethod, autosaveQuick, blocked, autoCl
         </div>
 
         <div className="output-row" style={{ alignItems: 'center' }}>
-          <div style={{ width: '100%' }}></div>
+          <div style={{ width: '100%' }}>
+            {suggestion && (
+              <div className="muted" role="note">
+                {suggestion.canonical && suggestion.canonical !== postfix && (
+                  <span>{t('postfixSuggestion')} <button className="btn small" onClick={() => setPostfix(suggestion.canonical!)}>{suggestion.canonical}</button></span>
+                )}
+                {suggestion.matches.length > 0 && <span style={{ marginLeft: 6 }}>{t('postfixMatchesSaved')}: {suggestion.matches.map(m => m.label).join(', ')}</span>}
+              </div>
+            )}
+            {methods.find(m => m.id === method)?.highValue && <div className="muted" role="note" style={{ color: 'var(--warn)' }}>{t('highValueMethodWarning')}</div>}
+          </div>
         </div>
       </div>
 
@@ -0,0 +1,14 @@
+import { invoke } from '../bridge'
+
+type SetupArgs = { viewerPassword: string, masterPassword: string }
+
+// Saves a new master and returns its fingerprint. A master that is not in NFC form (decomposed
+// accents and the like) is refused with code `needs_normalization`; `askNormalize` then picks
+// the stored form once.
+export async function saveNewMaster(args: SetupArgs, askNormalize: () => boolean): Promise<string> {
+  try { return await invoke<string>('setup_set_master', args) }
+  catch (err: any) {
+    if (err?.code !== 'needs_normalization') throw err
+    return invoke<string>('setup_set_master', { ...args, normalize: askNormalize() })
+  }
+}